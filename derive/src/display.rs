@@ -0,0 +1,346 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use regex_syntax::ast::{parse::Parser, Ast, GroupKind, RepetitionKind};
+use syn::{Data, DataEnum, DeriveInput, Expr, Fields, LitStr};
+
+use crate::{
+    struct_fields, struct_parser, variants, Field, FieldDefault, FieldParser, Pattern,
+    StructParser,
+};
+
+/// A part of the text matched by a regex
+enum Piece {
+    Text(String),
+    /// Capture group, with its name
+    Group(Option<String>),
+    /// Optional part, as `(?:, (?<weight>[0-9]+))?`
+    Optional(Vec<Piece>),
+}
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let Fields::Named(fields) = &data.fields else {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "AocDisplay needs a struct with named fields",
+                ));
+            };
+            let struct_parser = struct_parser(input)?;
+            let fields = struct_fields(fields, &struct_parser)?;
+            match struct_parser {
+                StructParser::Delim(delim, write) => pieces(&fields, &delim, write.as_ref())?,
+                StructParser::Re(re) => regex_struct(&fields, &re)?,
+            }
+        }
+        Data::Enum(data) => expand_enum(data)?,
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                name.span(),
+                "AocDisplay can only be derived for structs and enums",
+            ))
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote_spanned! {Span::mixed_site()=>
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+/// Writes the fields between the delimiters, or between `write` if given, the matching ones as
+/// well as the ordered ones
+fn pieces(fields: &[Field], delim: &Expr, write: Option<&LitStr>) -> syn::Result<TokenStream2> {
+    let can_be_left_out = |field: &Field| {
+        field.default.is_some() || matches!(field.parser, FieldParser::CollectRemaining)
+    };
+    let ordered = !fields
+        .iter()
+        .all(|field| matches!(field.parser, FieldParser::Matching(_)));
+    // the pieces of the ordered fields go to the first fields that can be left out
+    let unwritable = fields
+        .iter()
+        .enumerate()
+        .find(|(i, field)| field.optional && fields[i + 1..].iter().any(can_be_left_out));
+    if let Some((_, field)) = unwritable.filter(|_| ordered) {
+        return Err(syn::Error::new(
+            field.ident.span(),
+            "cannot write back a `None` in this field, a field after it that can be left out \
+             would be parsed in its place",
+        ));
+    }
+    let separate = quote_spanned! {Span::mixed_site()=>
+        aoc23::fields::separate(f, &mut first, delim)?;
+    };
+    let writes = fields
+        .iter()
+        .map(|field| {
+            let ident = &field.ident;
+            Ok(match &field.parser {
+                FieldParser::CollectRemaining => quote_spanned! {Span::mixed_site()=>
+                    for value in &self.#ident {
+                        #separate
+                        ::std::write!(f, "{}", value)?;
+                    }
+                },
+                _ if field.optional => {
+                    let value = write_value(field, quote_spanned!(Span::mixed_site()=> value))?;
+                    quote_spanned! {Span::mixed_site()=>
+                        if let Some(value) = &self.#ident {
+                            #separate
+                            #value
+                        }
+                    }
+                }
+                _ => {
+                    let value =
+                        write_value(field, quote_spanned!(Span::mixed_site()=> &self.#ident))?;
+                    if ordered {
+                        quote!(#separate #value)
+                    } else {
+                        // parsed back from its default when matched by no piece
+                        let default = match &field.default {
+                            Some(FieldDefault::Expr(expr)) => quote!(#expr),
+                            _ => quote!(::std::default::Default::default()),
+                        };
+                        quote_spanned! {Span::mixed_site()=>
+                            if <_ as ::std::cmp::PartialEq>::ne(&self.#ident, &#default) {
+                                #separate
+                                #value
+                            }
+                        }
+                    }
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    // a piece is still written when all the matching fields have their default
+    let fallback = match fields.first() {
+        Some(field) if !ordered && !field.optional => {
+            let ident = &field.ident;
+            let value = write_value(field, quote_spanned!(Span::mixed_site()=> &self.#ident))?;
+            quote_spanned! {Span::mixed_site()=>
+                if first {
+                    #value
+                }
+            }
+        }
+        _ => quote!(),
+    };
+    let delim = match write {
+        Some(write) => quote!(#write),
+        None => quote!(#delim),
+    };
+    Ok(quote_spanned! {Span::mixed_site()=>
+        let delim = #delim;
+        let mut first = true;
+        #(#writes)*
+        #fallback
+        Ok(())
+    })
+}
+
+/// Writes the text of the regex of the struct, with the fields in place of their group
+fn regex_struct(fields: &[Field], re: &LitStr) -> syn::Result<TokenStream2> {
+    let group = |name: Option<&str>| {
+        let field = name
+            .and_then(|name| fields.iter().find(|field| field.ident == name))
+            .ok_or_else(|| syn::Error::new(re.span(), "cannot write back an unnamed group"))?;
+        let ident = &field.ident;
+        if field.optional {
+            let value = write_value(field, quote_spanned!(Span::mixed_site()=> value))?;
+            Ok((
+                quote_spanned! {Span::mixed_site()=>
+                    if let Some(value) = &self.#ident {
+                        #value
+                    }
+                },
+                Some(quote_spanned!(Span::mixed_site()=> self.#ident.is_some())),
+            ))
+        } else {
+            let value = write_value(field, quote_spanned!(Span::mixed_site()=> &self.#ident))?;
+            Ok((value, None))
+        }
+    };
+    let text = write_template(&template(re)?, &group)?;
+    Ok(quote_spanned! {Span::mixed_site()=>
+        #text
+        Ok(())
+    })
+}
+
+fn expand_enum(data: &DataEnum) -> syn::Result<TokenStream2> {
+    let arms = variants(data)?
+        .into_iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let (pattern, value) = if variant.has_field {
+                (
+                    quote_spanned!(Span::mixed_site()=> Self::#ident(value)),
+                    quote_spanned!(Span::mixed_site()=> ::std::write!(f, "{}", value)?;),
+                )
+            } else {
+                (quote!(Self::#ident), quote!())
+            };
+            let text = match &variant.pattern {
+                Pattern::Prefix(prefix) => quote_spanned! {Span::mixed_site()=>
+                    f.write_str(#prefix)?;
+                    #value
+                },
+                Pattern::Re(re) => {
+                    let group = |_: Option<&str>| {
+                        if variant.has_field {
+                            Ok((value.clone(), None))
+                        } else {
+                            Err(syn::Error::new(
+                                re.span(),
+                                "cannot write back a group of a variant without a field",
+                            ))
+                        }
+                    };
+                    write_template(&single_group(re)?, &group)?
+                }
+            };
+            Ok(quote_spanned! {Span::mixed_site()=>
+                #pattern => {
+                    #text
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok(quote_spanned! {Span::mixed_site()=>
+        match self {
+            #(#arms)*
+        }
+        Ok(())
+    })
+}
+
+/// Writes `value`, a reference to the value of a field, the way it is parsed
+fn write_value(field: &Field, value: TokenStream2) -> syn::Result<TokenStream2> {
+    Ok(match &field.parser {
+        FieldParser::Next | FieldParser::Trim => quote_spanned! {Span::mixed_site()=>
+            ::std::write!(f, "{}", #value)?;
+        },
+        FieldParser::Collect(sub_delim, write) => {
+            let sub_delim = match write {
+                Some(write) => quote!(#write),
+                None => quote!(#sub_delim),
+            };
+            quote_spanned! {Span::mixed_site()=>
+                aoc23::fields::write_joined(f, #value, #sub_delim)?;
+            }
+        }
+        FieldParser::CollectLines => quote_spanned! {Span::mixed_site()=>
+            aoc23::fields::write_joined(f, #value, '\n')?;
+        },
+        FieldParser::Re(re) | FieldParser::Matching(re) => {
+            let group = |_: Option<&str>| {
+                Ok((
+                    quote_spanned!(Span::mixed_site()=> ::std::write!(f, "{}", #value)?;),
+                    None,
+                ))
+            };
+            write_template(&single_group(re)?, &group)?
+        }
+        FieldParser::CollectRemaining => unreachable!("written by the caller"),
+    })
+}
+
+/// The template of the regex of a field, whose only group is the field
+fn single_group(re: &LitStr) -> syn::Result<Vec<Piece>> {
+    match regex::Regex::new(&re.value()) {
+        Ok(regex) if regex.captures_len() > 2 => Err(syn::Error::new(
+            re.span(),
+            "cannot write back a regex with more than one group",
+        )),
+        _ => template(re),
+    }
+}
+
+/// The pieces of the text matched by a regex, which can only be made of literals, groups and
+/// optional parts
+fn template(re: &LitStr) -> syn::Result<Vec<Piece>> {
+    let pattern = re.value();
+    let ast = Parser::new()
+        .parse(&pattern)
+        .map_err(|err| syn::Error::new(re.span(), err))?;
+    let mut pieces = Vec::new();
+    add_pieces(&ast, &mut pieces).map_err(|span| {
+        let part = &pattern[span.start.offset..span.end.offset];
+        syn::Error::new(
+            re.span(),
+            format!("cannot write back `{part}`, only literals, groups and optional parts"),
+        )
+    })?;
+    Ok(pieces)
+}
+
+fn add_pieces(ast: &Ast, pieces: &mut Vec<Piece>) -> Result<(), regex_syntax::ast::Span> {
+    match ast {
+        Ast::Empty(_) | Ast::Flags(_) | Ast::Assertion(_) => {}
+        Ast::Literal(literal) => match pieces.last_mut() {
+            Some(Piece::Text(text)) => text.push(literal.c),
+            _ => pieces.push(Piece::Text(literal.c.into())),
+        },
+        Ast::Concat(concat) => {
+            for ast in &concat.asts {
+                add_pieces(ast, pieces)?;
+            }
+        }
+        Ast::Group(group) => match &group.kind {
+            GroupKind::CaptureIndex(_) => pieces.push(Piece::Group(None)),
+            GroupKind::CaptureName { name, .. } => {
+                pieces.push(Piece::Group(Some(name.name.clone())))
+            }
+            GroupKind::NonCapturing(_) => add_pieces(&group.ast, pieces)?,
+        },
+        Ast::Repetition(repetition) if repetition.op.kind == RepetitionKind::ZeroOrOne => {
+            let mut optional = Vec::new();
+            add_pieces(&repetition.ast, &mut optional)?;
+            pieces.push(Piece::Optional(optional));
+        }
+        ast => return Err(*ast.span()),
+    }
+    Ok(())
+}
+
+/// Code writing a group, and the condition for it to be written if any
+type GroupWriter<'a> =
+    dyn Fn(Option<&str>) -> syn::Result<(TokenStream2, Option<TokenStream2>)> + 'a;
+
+/// Writes the pieces, an optional part only when all its groups are written
+fn write_template(pieces: &[Piece], group: &GroupWriter) -> syn::Result<TokenStream2> {
+    pieces
+        .iter()
+        .map(|piece| match piece {
+            Piece::Text(text) => Ok(quote_spanned!(Span::mixed_site()=> f.write_str(#text)?;)),
+            Piece::Group(name) => Ok(group(name.as_deref())?.0),
+            Piece::Optional(optional) => {
+                let conditions = optional
+                    .iter()
+                    .filter_map(|piece| match piece {
+                        Piece::Group(name) => {
+                            group(name.as_deref()).map(|(_, cond)| cond).transpose()
+                        }
+                        _ => None,
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+                let text = write_template(optional, group)?;
+                if conditions.is_empty() {
+                    Ok(text)
+                } else {
+                    Ok(quote_spanned! {Span::mixed_site()=>
+                        if #(#conditions)&&* {
+                            #text
+                        }
+                    })
+                }
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,784 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use regex_syntax::ast::{Ast, GroupKind};
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    spanned::Spanned,
+    Data, DataEnum, DeriveInput, Expr, Fields, FieldsNamed, Ident, Lit, LitStr, Token, Type,
+};
+
+mod display;
+
+/// Implements `FromStr` for a struct whose fields are parsed from the pieces of the string
+/// between the delimiters given by `#[delim(...)]`, or for an enum whose variant is chosen by
+/// a prefix or a regex.
+///
+/// Each field tells how it is parsed with a `#[parse(...)]` attribute:
+/// - `#[parse()]`: the next piece
+/// - `#[parse(trim)]`: the next piece without its surrounding whitespace
+/// - `#[parse(collect(','))]`: the non-blank parts of the next piece between the delimiters,
+///   which can also be a string
+/// - `#[parse(collect(lines))]`: the non-empty lines of the next piece
+/// - `#[parse(collect(remaining))]`: all the remaining pieces
+/// - `#[parse(re("..."))]`: the first group of the regex in the next piece
+///
+/// A field can be left out of the string when it has a default, as in `#[parse(default)]` or
+/// `#[parse(trim, default = 1)]`, or when it is an `Option`. Such fields are only taken from the
+/// string when it has more pieces than the other fields need, from the first one.
+///
+/// When every field is given a bare regex instead, as in `#[parse("([0-9]+) red")]`, each piece
+/// sets the first field whose regex matches it, and the fields matched by no piece get their
+/// default value.
+///
+/// A struct can instead be parsed with a regex, as in `#[parse(re("(?<x>[0-9]+),(?<y>[0-9]+)"))]`
+/// in place of `#[delim(...)]`, each field being parsed from the named group of the same name.
+/// Their `#[parse(...)]` attributes are then optional, and apply to the text of their group.
+///
+/// The variants of an enum are tried in order, and are chosen with `#[parse(prefix = "%")]` by
+/// the start of the string, or with `#[parse(re("..."))]` by a regex. The field of a variant
+/// holding one is parsed from the rest of the string, or from the first group of the regex. A
+/// variant without a field is only chosen by its prefix when nothing follows it.
+///
+/// A type with a lifetime implements `ParseBorrowed` instead of `FromStr`, with a
+/// `parse(&'a str)` that can keep slices of the string: its fields of type `&'a str` are not
+/// copied, and its fields of other types with a lifetime are parsed with their own `parse`.
+///
+/// The delimiters of `#[delim(...)]` and `collect(...)` can be given another text to be written
+/// back with by `AocDisplay`, as in `#[delim(':', write = ": ")]`. The fields must then accept
+/// the spaces around them when parsed, with `trim` or a regex.
+///
+/// The errors name the field, the group or the variant that failed to parse.
+#[proc_macro_derive(AocParse, attributes(delim, parse))]
+pub fn derive_aoc_parse(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Display` for a type deriving `AocParse`, writing it back with the same
+/// delimiters, prefixes and regexes, so that it parses back to the same value.
+///
+/// The regexes written back can only hold literals, groups and optional parts such as
+/// `(?: w(?<weight>[0-9]+))?`, which are written when the `Option`s of their groups are `Some`.
+/// The fields that can be left out are written when they are set: an `Option` when it is `Some`,
+/// a field with a default always. An `Option` can thus not be followed by other fields that can
+/// be left out, which would be parsed in its place when it is `None`.
+///
+/// The fields given a bare regex are only written when they differ from their default, which
+/// they get back when parsed, but for the first one when all of them have their default.
+#[proc_macro_derive(AocDisplay, attributes(delim, parse))]
+pub fn derive_aoc_display(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    display::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+enum FieldParser {
+    Next,
+    Trim,
+    /// Delimiter, and the one to write back if different
+    Collect(Lit, Option<LitStr>),
+    CollectLines,
+    CollectRemaining,
+    Re(LitStr),
+    /// Regex of a field of a struct whose pieces are matched against all the fields
+    Matching(LitStr),
+}
+
+/// Value of a field missing from the string
+enum FieldDefault {
+    /// `Default::default()`
+    Default,
+    Expr(Expr),
+    /// `None` for an `Option`
+    None,
+}
+
+struct Field {
+    ident: Ident,
+    parser: FieldParser,
+    default: Option<FieldDefault>,
+    /// Whether the field is an `Option`
+    optional: bool,
+    /// Whether the field borrows from the string
+    borrows: bool,
+}
+
+impl Parse for FieldParser {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() || is_default(input) {
+            return Ok(FieldParser::Next);
+        }
+        if input.peek(LitStr) {
+            return Ok(FieldParser::Matching(regex(input.parse()?, true)?));
+        }
+        let kind: Ident = input.parse()?;
+        match kind.to_string().as_str() {
+            "trim" => Ok(FieldParser::Trim),
+            "collect" => {
+                let content;
+                parenthesized!(content in input);
+                if content.peek(Ident) {
+                    let what: Ident = content.parse()?;
+                    match what.to_string().as_str() {
+                        "lines" => Ok(FieldParser::CollectLines),
+                        "remaining" => Ok(FieldParser::CollectRemaining),
+                        _ => Err(syn::Error::new(
+                            what.span(),
+                            "expected `lines`, `remaining` or a delimiter",
+                        )),
+                    }
+                } else {
+                    match content.parse()? {
+                        lit @ (Lit::Char(_) | Lit::Str(_)) => {
+                            Ok(FieldParser::Collect(lit, write_delim(&content)?))
+                        }
+                        lit => Err(syn::Error::new(
+                            lit.span(),
+                            "expected a char or a string delimiter",
+                        )),
+                    }
+                }
+            }
+            "re" => {
+                let content;
+                parenthesized!(content in input);
+                Ok(FieldParser::Re(regex(content.parse()?, true)?))
+            }
+            _ => Err(syn::Error::new(
+                kind.span(),
+                "expected `trim`, `collect(...)`, `re(...)`, `default` or a regex",
+            )),
+        }
+    }
+}
+
+/// The delimiter to write back following a delimiter, as in `, write = ": "`
+fn write_delim(input: ParseStream) -> syn::Result<Option<LitStr>> {
+    if input.is_empty() {
+        return Ok(None);
+    }
+    input.parse::<Token![,]>()?;
+    let ident: Ident = input.parse()?;
+    if ident != "write" {
+        return Err(syn::Error::new(ident.span(), "expected `write`"));
+    }
+    input.parse::<Token![=]>()?;
+    Ok(Some(input.parse()?))
+}
+
+fn is_default(input: ParseStream) -> bool {
+    input
+        .fork()
+        .parse::<Ident>()
+        .is_ok_and(|ident| ident == "default")
+}
+
+/// Parses the attribute of a field: its parser, then its default
+fn parse_field_attr(input: ParseStream) -> syn::Result<(FieldParser, Option<FieldDefault>)> {
+    let parser = input.parse()?;
+    if input.is_empty() {
+        return Ok((parser, None));
+    }
+    if !matches!(parser, FieldParser::Next) || !is_default(input) {
+        input.parse::<Token![,]>()?;
+    }
+    let ident: Ident = input.parse()?;
+    if ident != "default" {
+        return Err(syn::Error::new(ident.span(), "expected `default`"));
+    }
+    if input.parse::<Option<Token![=]>>()?.is_some() {
+        Ok((parser, Some(FieldDefault::Expr(input.parse()?))))
+    } else {
+        Ok((parser, Some(FieldDefault::Default)))
+    }
+}
+
+/// Regex or prefix choosing an enum variant, or regex of a struct
+enum Pattern {
+    Prefix(LitStr),
+    Re(LitStr),
+}
+
+impl Parse for Pattern {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kind: Ident = input.parse()?;
+        match kind.to_string().as_str() {
+            "prefix" => {
+                input.parse::<Token![=]>()?;
+                Ok(Pattern::Prefix(input.parse()?))
+            }
+            "re" => {
+                let content;
+                parenthesized!(content in input);
+                Ok(Pattern::Re(content.parse()?))
+            }
+            _ => Err(syn::Error::new(
+                kind.span(),
+                "expected `prefix = \"...\"` or `re(\"...\")`",
+            )),
+        }
+    }
+}
+
+/// Checks a regex, which must capture a field in its first group if `capturing`
+fn regex(lit: LitStr, capturing: bool) -> syn::Result<LitStr> {
+    match regex::Regex::new(&lit.value()) {
+        Ok(re) if re.captures_len() > 1 || !capturing => Ok(lit),
+        Ok(_) => Err(syn::Error::new(
+            lit.span(),
+            "the regex needs a group capturing the field",
+        )),
+        Err(err) => Err(syn::Error::new(lit.span(), err)),
+    }
+}
+
+/// Whether the type of a field is an `Option`
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Whether a type borrows from the string, having a lifetime
+fn borrows(ty: &Type) -> bool {
+    fn has_lifetime(tokens: TokenStream2) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Punct(punct) => punct.as_char() == '\'',
+            TokenTree::Group(group) => has_lifetime(group.stream()),
+            _ => false,
+        })
+    }
+    has_lifetime(ty.to_token_stream())
+}
+
+/// The helper `name` of `aoc23::fields`, or its variant for the fields borrowing from the string
+fn helper(name: &str, borrows: bool) -> TokenStream2 {
+    let name = if borrows {
+        format_ident!("{name}_borrowed")
+    } else {
+        format_ident!("{name}")
+    };
+    quote_spanned!(Span::mixed_site()=> aoc23::fields::#name)
+}
+
+fn regex_static(re: &LitStr) -> TokenStream2 {
+    quote_spanned! {Span::mixed_site()=>
+        static RE: ::std::sync::LazyLock<aoc23::fields::Regex> =
+            ::std::sync::LazyLock::new(|| aoc23::fields::Regex::new(#re).unwrap());
+    }
+}
+
+/// How the fields of a struct are cut out of the string
+enum StructParser {
+    /// Delimiter, and the one to write back if different
+    Delim(Expr, Option<LitStr>),
+    /// Regex with a named group per field
+    Re(LitStr),
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let Fields::Named(fields) = &data.fields else {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "AocParse needs a struct with named fields",
+                ));
+            };
+            expand_struct(fields, struct_parser(input)?)?
+        }
+        Data::Enum(data) => expand_enum(data)?,
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                name.span(),
+                "AocParse can only be derived for structs and enums",
+            ))
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut lifetimes = input.generics.lifetimes().map(|param| &param.lifetime);
+    match (lifetimes.next(), lifetimes.next()) {
+        (None, _) => Ok(quote_spanned! {Span::mixed_site()=>
+            impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+                type Err = ::anyhow::Error;
+
+                fn from_str(string: &str) -> ::anyhow::Result<Self> {
+                    #body
+                }
+            }
+        }),
+        (Some(lifetime), None) => Ok(quote_spanned! {Span::mixed_site()=>
+            impl #impl_generics aoc23::fields::ParseBorrowed<#lifetime> for #name #ty_generics
+            #where_clause
+            {
+                fn parse(string: &#lifetime str) -> ::anyhow::Result<Self> {
+                    #body
+                }
+            }
+        }),
+        (Some(_), Some(lifetime)) => Err(syn::Error::new(
+            lifetime.span(),
+            "AocParse can only borrow from the string with a single lifetime",
+        )),
+    }
+}
+
+fn struct_parser(input: &DeriveInput) -> syn::Result<StructParser> {
+    let find = |name| input.attrs.iter().find(|attr| attr.path().is_ident(name));
+    match (find("delim"), find("parse")) {
+        (Some(delim), None) => delim.parse_args_with(|input: ParseStream| {
+            Ok(StructParser::Delim(input.parse()?, write_delim(input)?))
+        }),
+        (None, Some(attr)) => match attr.parse_args()? {
+            Pattern::Re(re) => Ok(StructParser::Re(regex(re, false)?)),
+            Pattern::Prefix(prefix) => Err(syn::Error::new(
+                prefix.span(),
+                "a struct is parsed with #[parse(re(\"...\"))], prefixes choose enum variants",
+            )),
+        },
+        (Some(delim), Some(_)) => Err(syn::Error::new(
+            delim.span(),
+            "a struct is parsed either with #[delim(...)] or with #[parse(re(...))]",
+        )),
+        (None, None) => Err(syn::Error::new(
+            input.ident.span(),
+            "missing #[delim(...)] giving the delimiter between the fields, \
+             or #[parse(re(...))] with a named group per field",
+        )),
+    }
+}
+
+/// The fields of a struct, with how they are parsed
+fn struct_fields(fields: &FieldsNamed, struct_parser: &StructParser) -> syn::Result<Vec<Field>> {
+    fields
+        .named
+        .iter()
+        .map(|field| {
+            let attr = field
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("parse"));
+            let (parser, default) = match (attr, struct_parser) {
+                (Some(attr), _) => attr.parse_args_with(parse_field_attr)?,
+                // the fields of a regex are parsed from their group by default
+                (None, StructParser::Re(_)) => (FieldParser::Next, None),
+                (None, StructParser::Delim(..)) => {
+                    return Err(syn::Error::new(
+                        field.span(),
+                        "missing #[parse(...)] telling how to parse the field",
+                    ))
+                }
+            };
+            let default = match default {
+                None if is_option(&field.ty) => Some(FieldDefault::None),
+                default => default,
+            };
+            match (&parser, attr) {
+                (FieldParser::CollectRemaining, Some(attr)) if default.is_some() => {
+                    return Err(syn::Error::new(
+                        attr.span(),
+                        "collect(remaining) already accepts a missing field",
+                    ))
+                }
+                (FieldParser::CollectRemaining | FieldParser::Matching(_), Some(attr))
+                    if matches!(struct_parser, StructParser::Re(_)) =>
+                {
+                    return Err(syn::Error::new(
+                        attr.span(),
+                        "the field is parsed from its group of the regex of the struct",
+                    ))
+                }
+                _ => {}
+            }
+            let ident = field.ident.clone().expect("the fields are named");
+            Ok(Field {
+                ident,
+                parser,
+                default,
+                optional: is_option(&field.ty),
+                borrows: borrows(&field.ty),
+            })
+        })
+        .collect()
+}
+
+fn expand_struct(fields: &FieldsNamed, struct_parser: StructParser) -> syn::Result<TokenStream2> {
+    let fields = struct_fields(fields, &struct_parser)?;
+    let delim = match struct_parser {
+        StructParser::Delim(delim, _) => delim,
+        StructParser::Re(re) => return regex_struct(&fields, &re),
+    };
+    let is_matching = |field: &&Field| matches!(field.parser, FieldParser::Matching(_));
+    match fields.iter().filter(is_matching).count() {
+        0 => Ok(ordered(&fields, &delim)),
+        n if n == fields.len() => Ok(matching(&fields, &delim)),
+        _ => {
+            let field = fields.iter().find(|field| !is_matching(field)).unwrap();
+            Err(syn::Error::new(
+                field.ident.span(),
+                "either all the fields are parsed with a bare regex, or none",
+            ))
+        }
+    }
+}
+
+/// Parses `source`, the text of a field, `what` naming it in the errors
+fn field_value(field: &Field, source: TokenStream2, what: &str) -> TokenStream2 {
+    let parse = helper("parse", field.borrows);
+    let collect = helper("collect", field.borrows);
+    match &field.parser {
+        FieldParser::Next => quote_spanned! {Span::mixed_site()=>
+            #parse(#source, #what)?
+        },
+        FieldParser::Trim => quote_spanned! {Span::mixed_site()=>
+            #parse(#source.trim(), #what)?
+        },
+        FieldParser::Collect(sub_delim, _) => quote_spanned! {Span::mixed_site()=>
+            #collect(#source.split(#sub_delim).filter(|s| !s.trim().is_empty()), #what)?
+        },
+        FieldParser::CollectLines => quote_spanned! {Span::mixed_site()=>
+            #collect(#source.lines().filter(|s| !s.is_empty()), #what)?
+        },
+        FieldParser::Re(re) => {
+            let regex_static = regex_static(re);
+            quote_spanned! {Span::mixed_site()=>
+                {
+                    #regex_static
+                    #parse(aoc23::fields::capture(&RE, #source, #what)?, #what)?
+                }
+            }
+        }
+        FieldParser::CollectRemaining | FieldParser::Matching(_) => {
+            unreachable!("the callers parse these fields themselves")
+        }
+    }
+}
+
+/// The value of a field that has a default, in `Some` for an `Option`, and its default
+fn with_default(value: TokenStream2, default: &FieldDefault) -> (TokenStream2, TokenStream2) {
+    match default {
+        FieldDefault::Default => (value, quote!(::std::default::Default::default())),
+        FieldDefault::Expr(expr) => (value, quote!(#expr)),
+        FieldDefault::None => (quote!(Some(#value)), quote!(None)),
+    }
+}
+
+/// Parses the fields from the pieces, in order
+fn ordered(fields: &[Field], delim: &Expr) -> TokenStream2 {
+    let required = fields
+        .iter()
+        .filter(|field| {
+            field.default.is_none() && !matches!(field.parser, FieldParser::CollectRemaining)
+        })
+        .count();
+    let values = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let what = format!("field `{ident}`");
+        let value = match &field.parser {
+            FieldParser::CollectRemaining => {
+                let collect = helper("collect", field.borrows);
+                quote_spanned! {Span::mixed_site()=>
+                    #collect(&mut pieces, #what)?
+                }
+            }
+            _ => {
+                let next = quote_spanned! {Span::mixed_site()=>
+                    aoc23::fields::next(&mut pieces, string, #what, delim)?
+                };
+                field_value(field, next, &what)
+            }
+        };
+        let value = match &field.default {
+            None => value,
+            Some(default) => {
+                let (value, default) = with_default(value, default);
+                quote_spanned! {Span::mixed_site()=>
+                    if spare > 0 {
+                        spare -= 1;
+                        #value
+                    } else {
+                        #default
+                    }
+                }
+            }
+        };
+        quote! { let #ident = #value; }
+    });
+    // the number of pieces of the fields that can be left out
+    let spare = fields.iter().any(|field| field.default.is_some()).then(|| {
+        quote_spanned! {Span::mixed_site()=>
+            let mut spare = string.split(delim).count().saturating_sub(#required);
+        }
+    });
+    let idents = fields.iter().map(|field| &field.ident);
+    quote_spanned! {Span::mixed_site()=>
+        let delim = #delim;
+        #spare
+        #[allow(unused_mut)]
+        let mut pieces = string.split(delim);
+        #(#values)*
+        Ok(Self { #(#idents),* })
+    }
+}
+
+/// Parses each piece as the first field whose regex matches it
+fn matching(fields: &[Field], delim: &Expr) -> TokenStream2 {
+    let idents = fields.iter().map(|field| &field.ident).collect::<Vec<_>>();
+    let names = idents.iter().map(|ident| ident.to_string());
+    let matches = fields.iter().map(|field| {
+        let FieldParser::Matching(re) = &field.parser else {
+            unreachable!("not mixed with the ordered fields")
+        };
+        let ident = &field.ident;
+        let what = format!("field `{ident}`");
+        let parse = helper("parse", field.borrows);
+        let regex_static = regex_static(re);
+        quote_spanned! {Span::mixed_site()=>
+            #regex_static
+            if let Some(group) = RE.captures(piece).and_then(|cap| cap.get(1)) {
+                #ident = Some(#parse(group.as_str(), #what)?);
+                continue;
+            }
+        }
+    });
+    let values = fields.iter().map(|field| {
+        let ident = &field.ident;
+        match &field.default {
+            None | Some(FieldDefault::Default) => quote!(#ident.unwrap_or_default()),
+            Some(FieldDefault::Expr(expr)) => quote!(#ident.unwrap_or_else(|| #expr)),
+            Some(FieldDefault::None) => quote!(#ident),
+        }
+    });
+    quote_spanned! {Span::mixed_site()=>
+        #(let mut #idents = None;)*
+        for piece in string.split(#delim) {
+            #({ #matches })*
+            return Err(aoc23::fields::unmatched(piece, "fields", &[#(#names),*]));
+        }
+        Ok(Self { #(#idents: #values),* })
+    }
+}
+
+/// Parses each field from the group of the same name of the regex of the struct
+fn regex_struct(fields: &[Field], re: &LitStr) -> syn::Result<TokenStream2> {
+    let pattern = re.value();
+    let groups = regex::Regex::new(&pattern)
+        .expect("checked with the attribute")
+        .capture_names()
+        .flatten()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    if let Some(group) = groups
+        .iter()
+        .find(|group| !fields.iter().any(|field| field.ident == group))
+    {
+        return Err(syn::Error::new(
+            re.span(),
+            format!("the group `{group}` is not a field of the struct"),
+        ));
+    }
+    let values = fields
+        .iter()
+        .map(|field| {
+            let ident = &field.ident;
+            let name = ident.to_string();
+            if !groups.contains(&name) {
+                let Some(default) = &field.default else {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("no group `{name}` in the regex of the struct"),
+                    ));
+                };
+                let (_, default) = with_default(quote!(), default);
+                return Ok(quote! { let #ident = #default; });
+            }
+            let what = format!("group `{name}`");
+            let value = field_value(
+                field,
+                quote_spanned!(Span::mixed_site()=> group.as_str()),
+                &what,
+            );
+            let (value, missing) = match &field.default {
+                None => (
+                    value,
+                    quote_spanned! {Span::mixed_site()=>
+                        return Err(aoc23::fields::missing_group(string, #name))
+                    },
+                ),
+                Some(default) => with_default(value, default),
+            };
+            Ok(quote_spanned! {Span::mixed_site()=>
+                let #ident = match captures.name(#name) {
+                    Some(group) => #value,
+                    None => #missing,
+                };
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let prefixes = group_prefixes(&pattern)
+        .into_iter()
+        .map(|(group, prefix)| quote!((#group, #prefix)));
+    let regex_static = regex_static(re);
+    let idents = fields.iter().map(|field| &field.ident);
+    Ok(quote_spanned! {Span::mixed_site()=>
+        #regex_static
+        let Some(captures) = RE.captures(string) else {
+            return Err(aoc23::fields::unmatched_regex(string, &RE, &[#(#prefixes),*]));
+        };
+        #(#values)*
+        Ok(Self { #(#idents),* })
+    })
+}
+
+/// The named groups at the top level of `pattern`, each with the pattern up to its end.
+///
+/// When the string does not match, the first of these prefixes that does not match either
+/// tells which group failed.
+fn group_prefixes(pattern: &str) -> Vec<(String, String)> {
+    let Ok(ast) = regex_syntax::ast::parse::Parser::new().parse(pattern) else {
+        return Vec::new();
+    };
+    let items = match &ast {
+        Ast::Concat(concat) => concat.asts.iter().collect(),
+        ast => vec![ast],
+    };
+    items
+        .into_iter()
+        .filter_map(|ast| match ast {
+            Ast::Group(group) => match &group.kind {
+                GroupKind::CaptureName { name, .. } => Some((
+                    name.name.clone(),
+                    String::from(&pattern[..group.span.end.offset]),
+                )),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// A variant of an enum, with how it is chosen
+struct Variant {
+    ident: Ident,
+    pattern: Pattern,
+    /// Whether the variant holds a field
+    has_field: bool,
+    /// Whether its field borrows from the string
+    borrows: bool,
+}
+
+fn variants(data: &DataEnum) -> syn::Result<Vec<Variant>> {
+    data.variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let attr = variant
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("parse"))
+                .ok_or_else(|| {
+                    syn::Error::new(
+                        ident.span(),
+                        "missing #[parse(prefix = \"...\")] or #[parse(re(\"...\"))] choosing the variant",
+                    )
+                })?;
+            let (has_field, borrows) = match &variant.fields {
+                Fields::Unit => (false, false),
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    (true, borrows(&fields.unnamed[0].ty))
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "AocParse needs variants holding at most one unnamed field",
+                    ))
+                }
+            };
+            let pattern = match attr.parse_args()? {
+                Pattern::Re(re) => Pattern::Re(regex(re, has_field)?),
+                prefix => prefix,
+            };
+            Ok(Variant {
+                ident: ident.clone(),
+                pattern,
+                has_field,
+                borrows,
+            })
+        })
+        .collect()
+}
+
+/// Tries the variants in order
+fn expand_enum(data: &DataEnum) -> syn::Result<TokenStream2> {
+    let variants = variants(data)?;
+    let checks = variants.iter().map(|variant| {
+        let Variant {
+            ident,
+            has_field,
+            borrows,
+            ..
+        } = variant;
+        let what = format!("variant `{ident}`");
+        let parse = helper("parse", *borrows);
+        let value = |s: TokenStream2| {
+            if *has_field {
+                quote_spanned! {Span::mixed_site()=>
+                    Self::#ident(#parse(#s, #what)?)
+                }
+            } else {
+                quote!(Self::#ident)
+            }
+        };
+        match &variant.pattern {
+            Pattern::Prefix(prefix) if *has_field => {
+                let value = value(quote_spanned!(Span::mixed_site()=> rest));
+                quote_spanned! {Span::mixed_site()=>
+                    if let Some(rest) = string.strip_prefix(#prefix) {
+                        return Ok(#value);
+                    }
+                }
+            }
+            // without a field for the rest of the string, the prefix is the whole string
+            Pattern::Prefix(prefix) => quote_spanned! {Span::mixed_site()=>
+                if string == #prefix {
+                    return Ok(Self::#ident);
+                }
+            },
+            Pattern::Re(re) => {
+                let regex_static = regex_static(re);
+                let value = value(quote_spanned!(Span::mixed_site()=> group.as_str()));
+                let group = if *has_field {
+                    quote_spanned!(Span::mixed_site()=> .and_then(|cap| cap.get(1)))
+                } else {
+                    quote!()
+                };
+                quote_spanned! {Span::mixed_site()=>
+                    {
+                        #regex_static
+                        #[allow(unused_variables)]
+                        if let Some(group) = RE.captures(string) #group {
+                            return Ok(#value);
+                        }
+                    }
+                }
+            }
+        }
+    });
+    let names = variants.iter().map(|variant| variant.ident.to_string());
+    Ok(quote_spanned! {Span::mixed_site()=>
+        #(#checks)*
+        Err(aoc23::fields::unmatched(string, "variants", &[#(#names),*]))
+    })
+}
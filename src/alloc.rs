@@ -0,0 +1,150 @@
+use std::fmt;
+
+/// Allocations made while running one step, counted when the `alloc-stats` feature is enabled.
+///
+/// The counters are shared by all the threads of the process: run the days of `aoc run`
+/// with `--serial` to get the statistics of each day alone.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AllocStats {
+    pub count: u64,
+    pub bytes: u64,
+    /// Highest amount of memory allocated during the step and not yet freed
+    pub peak: u64,
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} allocs, {}, peak {}",
+            self.count,
+            format_bytes(self.bytes),
+            format_bytes(self.peak)
+        )
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.2} {}", UNITS[unit])
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        sync::atomic::{AtomicU64, Ordering::Relaxed},
+    };
+
+    use super::AllocStats;
+
+    pub struct CountingAllocator;
+
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+    static BYTES: AtomicU64 = AtomicU64::new(0);
+    static LIVE: AtomicU64 = AtomicU64::new(0);
+    static PEAK: AtomicU64 = AtomicU64::new(0);
+
+    fn allocated(size: usize) {
+        COUNT.fetch_add(1, Relaxed);
+        BYTES.fetch_add(size as u64, Relaxed);
+        let live = LIVE.fetch_add(size as u64, Relaxed) + size as u64;
+        PEAK.fetch_max(live, Relaxed);
+    }
+
+    fn freed(size: usize) {
+        LIVE.fetch_sub(size as u64, Relaxed);
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                allocated(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                allocated(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            freed(layout.size());
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                freed(layout.size());
+                allocated(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+        let live = LIVE.load(Relaxed);
+        PEAK.store(live, Relaxed);
+        let count = COUNT.load(Relaxed);
+        let bytes = BYTES.load(Relaxed);
+        let res = f();
+        let stats = AllocStats {
+            count: COUNT.load(Relaxed) - count,
+            bytes: BYTES.load(Relaxed) - bytes,
+            peak: PEAK.load(Relaxed).saturating_sub(live),
+        };
+        (res, Some(stats))
+    }
+}
+
+/// Runs `f`, returning the allocations it made if the `alloc-stats` feature is enabled
+#[cfg(feature = "alloc-stats")]
+pub use counting::measure;
+
+/// Runs `f`, returning the allocations it made if the `alloc-stats` feature is enabled
+#[cfg(not(feature = "alloc-stats"))]
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+    (f(), None)
+}
+
+#[cfg(test)]
+mod test_alloc {
+    use crate::alloc::format_bytes;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.50 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.00 MiB");
+    }
+
+    #[cfg(feature = "alloc-stats")]
+    #[test]
+    fn test_measure() {
+        let (v, stats) = crate::alloc::measure(|| vec![0u8; 4096]);
+        let stats = stats.unwrap();
+        assert!(stats.count >= 1);
+        assert!(stats.bytes >= 4096);
+        assert!(stats.peak >= 4096);
+        drop(v);
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+
+use crate::grid::Grid;
+
+/// Answer of a part, built with `into()` from what the part returns
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Unsigned(u128),
+    /// Only holds negative numbers, so that equal numbers are equal answers
+    Signed(i128),
+    Text(String),
+    /// Several lines, such as letters drawn on a grid
+    Grid(String),
+    /// The part is not solved yet
+    Unsolved,
+}
+
+impl Answer {
+    pub fn is_solved(&self) -> bool {
+        self != &Answer::Unsolved
+    }
+
+    /// The answer on a single line, for tables
+    pub fn one_line(&self) -> String {
+        match self {
+            Answer::Grid(grid) => format!("({} lines)", grid.lines().count()),
+            answer => answer.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Unsigned(n) => write!(f, "{n}"),
+            Answer::Signed(n) => write!(f, "{n}"),
+            Answer::Text(text) | Answer::Grid(text) => write!(f, "{text}"),
+            Answer::Unsolved => write!(f, "unsolved"),
+        }
+    }
+}
+
+macro_rules! from_integers {
+    ($($t:ty),* => |$n:ident| $answer:expr) => {
+        $(
+            impl From<$t> for Answer {
+                fn from($n: $t) -> Self {
+                    $answer
+                }
+            }
+
+            impl PartialEq<$t> for Answer {
+                fn eq(&self, other: &$t) -> bool {
+                    match self {
+                        Answer::Unsigned(n) => u128::try_from(*other).is_ok_and(|other| *n == other),
+                        Answer::Signed(n) => i128::try_from(*other).is_ok_and(|other| *n == other),
+                        _ => false,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_integers!(u8, u16, u32, u64, u128, usize => |n| Answer::Unsigned(n as u128));
+from_integers!(i8, i16, i32, i64, i128, isize => |n| if n < 0 {
+    Answer::Signed(n as i128)
+} else {
+    Answer::Unsigned(n as u128)
+});
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(String::from(text))
+    }
+}
+
+impl<Item> From<Grid<Item>> for Answer
+where
+    Grid<Item>: fmt::Display,
+{
+    fn from(grid: Grid<Item>) -> Self {
+        Answer::Grid(grid.to_string().trim_end().to_string())
+    }
+}
+
+#[cfg(test)]
+mod test_answer {
+    use crate::answer::Answer;
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(Answer::from(42u8), Answer::from(42i64));
+        assert_eq!(Answer::from(-3i32), Answer::Signed(-3));
+        assert_eq!(Answer::from(u128::MAX).to_string(), u128::MAX.to_string());
+        assert_eq!(Answer::from(47usize), 47);
+        assert_ne!(Answer::Unsolved, 0);
+        assert_eq!(Answer::from("EFJK").to_string(), "EFJK");
+        assert_eq!(Answer::Grid(String::from("#.\n.#")).one_line(), "(2 lines)");
+    }
+}
@@ -0,0 +1,253 @@
+use std::{collections::HashMap, fmt, sync::LazyLock};
+
+use anyhow::bail;
+use thiserror::Error;
+
+use crate::{runner::PartReport, Args, DayReport, Part, Result};
+
+/// Expected answers of the real inputs, from `answers.toml` at the root of the repository
+pub static ANSWERS: LazyLock<Answers> = LazyLock::new(|| {
+    Answers::parse(include_str!("../answers.toml")).expect("answers.toml should be valid")
+});
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("answers.toml:{line}: {err}")]
+pub struct AnswersError {
+    line: usize,
+    err: String,
+}
+
+/// Expected answers keyed by day and part.
+///
+/// The file is a small subset of TOML: one `[dayN]` table per day,
+/// holding `part1 = ...` and `part2 = ...` keys with integer or string values.
+#[derive(Debug, Default)]
+pub struct Answers {
+    expected: HashMap<(u32, Part), String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Fail { expected: String },
+    Missing,
+}
+
+impl CheckStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Fail { .. } => "FAIL",
+            CheckStatus::Missing => "MISSING",
+        }
+    }
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Fail { expected } => write!(f, "FAIL (expected {expected})"),
+            status => write!(f, "{}", status.name()),
+        }
+    }
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Answers, AnswersError> {
+        let mut answers = Answers::default();
+        let mut day = None;
+        for (i, line) in text.lines().enumerate() {
+            let error = |err: &str| AnswersError {
+                line: i + 1,
+                err: String::from(err),
+            };
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(table) = line.strip_prefix('[') {
+                let number = table
+                    .strip_suffix(']')
+                    .and_then(|t| t.trim().strip_prefix("day"))
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| error("expected a [dayN] table"))?;
+                day = Some(number);
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected a key = value pair"))?;
+            let part = match key.trim() {
+                "part1" => Part::Part1,
+                "part2" => Part::Part2,
+                key => return Err(error(&format!("unknown key '{key}'"))),
+            };
+            let value = value.trim();
+            let value = match value.strip_prefix('"') {
+                Some(s) => s
+                    .strip_suffix('"')
+                    .ok_or_else(|| error("unterminated string"))?,
+                None if value.parse::<i128>().is_ok() => value,
+                None => return Err(error(&format!("invalid value '{value}'"))),
+            };
+            let day = day.ok_or_else(|| error("key outside of a [dayN] table"))?;
+            if answers
+                .expected
+                .insert((day, part), String::from(value))
+                .is_some()
+            {
+                return Err(error("duplicate answer"));
+            }
+        }
+        Ok(answers)
+    }
+
+    pub fn expected(&self, day: u32, part: Part) -> Option<&str> {
+        self.expected.get(&(day, part)).map(String::as_str)
+    }
+
+    /// Compares the answer of a part with the expected one; an error is a failure
+    pub fn check(&self, day: u32, part: Part, report: &PartReport) -> CheckStatus {
+        match (self.expected(day, part), &report.answer) {
+            (None, _) => CheckStatus::Missing,
+            (Some(expected), Ok(answer)) if answer.to_string() == expected => CheckStatus::Pass,
+            (Some(expected), _) => CheckStatus::Fail {
+                expected: String::from(expected),
+            },
+        }
+    }
+
+    /// Statuses of the parts that were run, in part order
+    pub fn check_report(&self, day: u32, report: &DayReport) -> Vec<(Part, CheckStatus)> {
+        [(Part::Part1, &report.part1), (Part::Part2, &report.part2)]
+            .into_iter()
+            .filter_map(|(part, p)| Some((part, self.check(day, part, p.as_ref()?))))
+            .collect()
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    // a '#' inside a string value is not a comment
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Formats the statuses of a day on one line, along with the number of failed parts
+pub fn check_line(day: u32, report: &Result<DayReport>) -> (String, usize) {
+    let statuses = match report {
+        Ok(report) => ANSWERS.check_report(day, report),
+        Err(_) => return (format!("day {day:>2}: FAIL (parse error)"), 1),
+    };
+    let line = statuses
+        .iter()
+        .map(|(part, status)| format!("{}: {status}", part_name(*part)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let failures = statuses
+        .iter()
+        .filter(|(_, s)| matches!(s, CheckStatus::Fail { .. }))
+        .count();
+    (format!("day {day:>2}: {line}"), failures)
+}
+
+pub fn check_failures(failures: usize) -> Result<()> {
+    if failures > 0 {
+        bail!("{failures} answer(s) do not match answers.toml");
+    }
+    Ok(())
+}
+
+/// Runs a day on its real input and checks the parts having an expected answer.
+///
+/// Parts without an expected answer are not run at all, so that unsolved or
+/// very slow parts can be left out of the tests.
+pub fn check_real_input(
+    day: u32,
+    input: &str,
+    run: fn(&str, &Args) -> Result<DayReport>,
+) -> Result<()> {
+    let part = match (
+        ANSWERS.expected(day, Part::Part1),
+        ANSWERS.expected(day, Part::Part2),
+    ) {
+        (None, None) => return Ok(()),
+        (Some(_), None) => Some(Part::Part1),
+        (None, Some(_)) => Some(Part::Part2),
+        (Some(_), Some(_)) => None,
+    };
+    let args = Args {
+        part,
+        ..Default::default()
+    };
+    let report = run(input, &args)?;
+    let failures = ANSWERS
+        .check_report(day, &report)
+        .into_iter()
+        .filter_map(|(part, status)| match status {
+            CheckStatus::Fail { expected } => Some(format!(
+                "{}: expected {expected}, got {}",
+                part_name(part),
+                answer_or_error(&report, part)
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !failures.is_empty() {
+        bail!("day {day}: {}", failures.join("; "));
+    }
+    Ok(())
+}
+
+fn answer_or_error(report: &DayReport, part: Part) -> String {
+    let p = match part {
+        Part::Part1 => &report.part1,
+        Part::Part2 => &report.part2,
+    };
+    match p.as_ref().map(|p| &p.answer) {
+        Some(Ok(answer)) => answer.to_string(),
+        Some(Err(err)) => format!("error: {err:#}"),
+        None => String::from("nothing"),
+    }
+}
+
+pub fn part_name(part: Part) -> &'static str {
+    match part {
+        Part::Part1 => "part1",
+        Part::Part2 => "part2",
+    }
+}
+
+#[cfg(test)]
+mod test_answers {
+    use crate::{
+        answers::{Answers, AnswersError},
+        Part,
+    };
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = Answers::parse(
+            "# comment\n[day3]\npart1 = 4361 # trailing\npart2 = \"a # b\"\n\n[day 4]\n",
+        );
+        assert_eq!(
+            answers.unwrap_err(),
+            AnswersError {
+                line: 6,
+                err: String::from("expected a [dayN] table")
+            }
+        );
+        let answers = Answers::parse("[day3]\npart1 = 4361\npart2 = \"a # b\"\n").unwrap();
+        assert_eq!(answers.expected(3, Part::Part1), Some("4361"));
+        assert_eq!(answers.expected(3, Part::Part2), Some("a # b"));
+        assert_eq!(answers.expected(4, Part::Part1), None);
+        assert!(Answers::parse("part1 = 3").is_err());
+        assert!(Answers::parse("[day1]\npart1 = 3\npart1 = 4").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use aoc23::Day;
+
+macro_rules! register_days {
+    ($($module:ident = $number:literal at $path:literal,)*) => {
+        $(
+            #[path = $path]
+            mod $module;
+        )*
+
+        pub const DAYS: &[Day] = &[$(
+            Day {
+                number: $number,
+                input: $module::INPUT,
+                run: $module::run,
+            },
+        )*];
+
+        /// One test per day, checking the answers of the real input against answers.toml
+        #[cfg(test)]
+        mod real_input {
+            $(
+                #[test]
+                fn $module() -> anyhow::Result<()> {
+                    aoc23::answers::check_real_input($number, super::$module::INPUT, super::$module::run)
+                }
+            )*
+        }
+    };
+}
+
+register_days! {
+    day1 = 1 at "../day1/main.rs",
+    day2 = 2 at "../day2/main.rs",
+    day3 = 3 at "../day3/main.rs",
+    day4 = 4 at "../day4/main.rs",
+    day5 = 5 at "../day5/main.rs",
+    day6 = 6 at "../day6/main.rs",
+    day7 = 7 at "../day7/main.rs",
+    day8 = 8 at "../day8/main.rs",
+    day9 = 9 at "../day9/main.rs",
+    day10 = 10 at "../day10/main.rs",
+    day11 = 11 at "../day11/main.rs",
+    day12 = 12 at "../day12/main.rs",
+    day13 = 13 at "../day13/main.rs",
+    day14 = 14 at "../day14/main.rs",
+    day15 = 15 at "../day15/main.rs",
+    day16 = 16 at "../day16/main.rs",
+    day17 = 17 at "../day17/main.rs",
+    day18 = 18 at "../day18/main.rs",
+    day19 = 19 at "../day19/main.rs",
+    day20 = 20 at "../day20/main.rs",
+    day21 = 21 at "../day21/main.rs",
+    day22 = 22 at "../day22/main.rs",
+    day23 = 23 at "../day23/main.rs",
+    day24 = 24 at "../day24/main.rs",
+}
@@ -0,0 +1,212 @@
+use std::{ops::RangeInclusive, path::PathBuf, time::Instant};
+
+use anyhow::bail;
+use aoc23::{
+    bench::print_bench,
+    capture::capture,
+    history,
+    output::{print_reports, Format},
+    print_errors, print_totals, read_input, Args, Day, DayReport, Result,
+};
+use clap::{Parser, Subcommand};
+use rayon::prelude::*;
+
+mod days;
+mod scaffold;
+
+// the examples and other tests of the days are run with their own binary, the days reach this
+// in place of the macro of aoc23 to leave them out
+macro_rules! day_tests {
+    ($($item:item)*) => {};
+}
+use day_tests;
+
+#[derive(Parser)]
+#[command(author, version, about = "Advent of Code 2023 runner", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run one or several days and print a summary of their answers and timings
+    Run(RunArgs),
+    /// Inspect the timings recorded with --label
+    #[command(subcommand)]
+    Perf(PerfCommand),
+    /// Create a new day from the day0 template and register it
+    New(NewArgs),
+}
+
+#[derive(Subcommand)]
+enum PerfCommand {
+    /// Print the timing change of each step between two labels, flagging regressions
+    Diff(DiffArgs),
+}
+
+#[derive(clap::Args)]
+struct DiffArgs {
+    /// Label of the reference timings
+    before: String,
+    /// Label of the timings to compare with the reference
+    after: String,
+    /// Slowdown in percent above which a step is a regression
+    #[arg(long, value_name = "PERCENT", default_value_t = 10.0)]
+    threshold: f64,
+    /// History file of the labelled timings [default: aoc-history.csv]
+    #[arg(long, value_name = "PATH")]
+    history: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+struct NewArgs {
+    #[arg(value_parser = clap::value_parser!(u32).range(1..=25))]
+    day: u32,
+    /// Root of the repository [default: the one aoc was built from]
+    #[arg(long, value_name = "PATH")]
+    root: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+struct RunArgs {
+    /// Day to run, or an inclusive range of days such as 1..=12
+    #[arg(value_parser = parse_days, required_unless_present = "all")]
+    days: Option<RangeInclusive<u32>>,
+    /// Run every registered day
+    #[arg(long, conflicts_with = "days")]
+    all: bool,
+    /// Run the days one after the other instead of concurrently, for accurate timings
+    #[arg(long)]
+    serial: bool,
+    #[command(flatten)]
+    args: Args,
+}
+
+fn parse_days(s: &str) -> Result<RangeInclusive<u32>> {
+    let range = if let Some((first, last)) = s.split_once("..=") {
+        first.parse()?..=last.parse()?
+    } else if let Some((first, end)) = s.split_once("..") {
+        first.parse()?..=(end.parse::<u32>()?.saturating_sub(1))
+    } else {
+        let day = s.parse()?;
+        day..=day
+    };
+    if range.is_empty() {
+        bail!("empty range of days '{s}'");
+    }
+    Ok(range)
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Run(run_args) => run(run_args),
+        Command::Perf(PerfCommand::Diff(diff_args)) => perf_diff(diff_args),
+        Command::New(new_args) => new_day(new_args),
+    }
+}
+
+fn new_day(args: NewArgs) -> Result<()> {
+    let root = args
+        .root
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")));
+    for path in scaffold::new_day(&root, args.day)? {
+        println!("    {}", path.display());
+    }
+    println!("Day {} is ready, rebuild aoc to run it", args.day);
+    Ok(())
+}
+
+fn perf_diff(args: DiffArgs) -> Result<()> {
+    let entries = history::load(&history::history_path(args.history.as_deref()))?;
+    for label in [&args.before, &args.after] {
+        if !entries.iter().any(|e| &e.label == label) {
+            bail!("no timings recorded under the label '{label}'");
+        }
+    }
+    let deltas = history::diff(&entries, &args.before, &args.after);
+    if deltas.is_empty() {
+        bail!(
+            "'{}' and '{}' have no step in common",
+            args.before,
+            args.after
+        );
+    }
+    let regressions = history::print_diff(&deltas, &args.before, &args.after, args.threshold);
+    if regressions > 0 {
+        bail!(
+            "{regressions} step(s) slower by more than {}%",
+            args.threshold
+        );
+    }
+    Ok(())
+}
+
+fn run(run_args: RunArgs) -> Result<()> {
+    let days = days::DAYS
+        .iter()
+        .filter(|day| {
+            run_args.all
+                || run_args
+                    .days
+                    .as_ref()
+                    .is_some_and(|r| r.contains(&day.number))
+        })
+        .collect::<Vec<_>>();
+    if days.is_empty() {
+        bail!("no registered day matches the selection");
+    }
+    let args = &run_args.args;
+    if args.input.is_some() && days.len() > 1 {
+        bail!("--input can only be used when running a single day");
+    }
+    if args.visualize && days.len() > 1 {
+        bail!("--visualize can only be used when running a single day");
+    }
+    let start = Instant::now();
+    let run_day = |day: &&Day| -> Result<(u32, Result<DayReport>, String)> {
+        let input = read_input(args.input.as_deref(), day.input)?;
+        let (report, output) = capture(|| (day.run)(&input, args));
+        Ok((day.number, report, output))
+    };
+    let results = if run_args.serial {
+        days.iter().map(run_day).collect::<Result<Vec<_>>>()?
+    } else {
+        days.par_iter().map(run_day).collect::<Result<Vec<_>>>()?
+    };
+    let wall_time = start.elapsed();
+    let (reports, outputs): (Vec<_>, Vec<_>) = results
+        .into_iter()
+        .map(|(day, report, output)| ((day, report), (day, output)))
+        .unzip();
+    for (day, output) in outputs.iter().filter(|(_, output)| !output.is_empty()) {
+        if args.format == Format::Text {
+            println!("\nOutput of day {day}:");
+            output.lines().for_each(|line| println!("    {line}"));
+        } else {
+            output
+                .lines()
+                .for_each(|line| eprintln!("day {day}: {line}"));
+        }
+    }
+    if args.format == Format::Text {
+        for (day, report) in &reports {
+            if let Ok(DayReport {
+                bench: Some(bench), ..
+            }) = report
+            {
+                print!("\nDay {day}");
+                print_bench(bench);
+            }
+        }
+    }
+    let res = print_reports(&reports, args);
+    if args.format == Format::Text {
+        if reports.len() > 1 {
+            print_totals(&reports, wall_time);
+        } else {
+            print_errors(&reports);
+        }
+    }
+    res
+}
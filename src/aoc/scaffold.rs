@@ -0,0 +1,140 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context as _};
+use aoc23::Result;
+
+const TEMPLATE_DAY: &str = "day0";
+const REGISTRY: &str = "src/aoc/days.rs";
+
+/// Creates `src/dayN` from the day0 template, and registers the day in Cargo.toml, the registry
+/// of the `aoc` runner and answers.toml. Returns the paths created or modified.
+///
+/// Nothing is written if any of these already knows the day.
+pub fn new_day(root: &Path, day: u32) -> Result<Vec<PathBuf>> {
+    let name = format!("day{day}");
+    let dir = root.join("src").join(&name);
+    if dir.exists() {
+        bail!("{} already exists", dir.display());
+    }
+    let template = read(&root.join("src").join(TEMPLATE_DAY).join("main.rs"))?;
+
+    let cargo_path = root.join("Cargo.toml");
+    let registry_path = root.join(REGISTRY);
+    let answers_path = root.join("answers.toml");
+    let edits = [
+        (add_bin(&read(&cargo_path)?, day)?, cargo_path),
+        (
+            add_registration(&read(&registry_path)?, day)?,
+            registry_path,
+        ),
+        (add_answers_stub(&read(&answers_path)?, day)?, answers_path),
+    ];
+
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let files = [
+        (template, dir.join("main.rs")),
+        (String::new(), dir.join("input.txt")),
+        (String::new(), dir.join("example.txt")),
+    ];
+    let mut written = Vec::new();
+    for (content, path) in files.into_iter().chain(edits) {
+        fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// Adds the `[[bin]]` of the day after the last one
+fn add_bin(cargo: &str, day: u32) -> Result<String> {
+    if cargo.contains(&format!("name = \"day{day}\"")) {
+        bail!("Cargo.toml already has a binary for day {day}");
+    }
+    let block = format!("[[bin]]\nname = \"day{day}\"\npath = \"src/day{day}/main.rs\"\n");
+    let Some(last_bin) = cargo.rfind("[[bin]]") else {
+        bail!("no [[bin]] in Cargo.toml to add day {day} after");
+    };
+    let mut cargo = String::from(cargo);
+    match cargo[last_bin..].find("\n\n") {
+        Some(blank_line) => cargo.insert_str(last_bin + blank_line + 1, &format!("\n{block}")),
+        None => {
+            if !cargo.ends_with('\n') {
+                cargo.push('\n');
+            }
+            cargo.push_str(&format!("\n{block}"));
+        }
+    }
+    Ok(cargo)
+}
+
+/// Adds the day after the last one of the `register_days!` invocation
+fn add_registration(registry: &str, day: u32) -> Result<String> {
+    let is_registration = |line: &str| {
+        let line = line.trim_start();
+        line.starts_with("day") && line.contains(" at \"")
+    };
+    let prefix = format!("day{day} = ");
+    if registry
+        .lines()
+        .any(|line| is_registration(line) && line.trim_start().starts_with(&prefix))
+    {
+        bail!("day {day} is already registered in {REGISTRY}");
+    }
+    let mut lines = registry.lines().collect::<Vec<_>>();
+    let Some(last) = lines.iter().rposition(|line| is_registration(line)) else {
+        bail!("no registered day in {REGISTRY} to add day {day} after");
+    };
+    let registration = format!("    day{day} = {day} at \"../day{day}/main.rs\",");
+    lines.insert(last + 1, &registration);
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Adds an empty table for the day at the end of answers.toml, where it is reported as MISSING
+fn add_answers_stub(answers: &str, day: u32) -> Result<String> {
+    let table = format!("[day{day}]");
+    if answers.lines().any(|line| line.trim() == table) {
+        bail!("answers.toml already has answers for day {day}");
+    }
+    let mut answers = String::from(answers.trim_end());
+    answers.push_str(&format!("\n\n{table}\n"));
+    Ok(answers)
+}
+
+#[cfg(test)]
+mod test_scaffold {
+    use crate::scaffold::{add_answers_stub, add_bin, add_registration};
+
+    #[test]
+    fn test_add_day() {
+        let cargo = "[[bin]]\nname = \"day1\"\npath = \"src/day1/main.rs\"\n\n\
+                     [profile.test]\nopt-level = 1\n";
+        assert_eq!(
+            add_bin(cargo, 2).unwrap(),
+            "[[bin]]\nname = \"day1\"\npath = \"src/day1/main.rs\"\n\n\
+             [[bin]]\nname = \"day2\"\npath = \"src/day2/main.rs\"\n\n\
+             [profile.test]\nopt-level = 1\n"
+        );
+        assert!(add_bin(cargo, 1).is_err());
+
+        let registry = "register_days! {\n    day1 = 1 at \"../day1/main.rs\",\n}\n";
+        assert_eq!(
+            add_registration(registry, 2).unwrap(),
+            "register_days! {\n    day1 = 1 at \"../day1/main.rs\",\n    \
+             day2 = 2 at \"../day2/main.rs\",\n}\n"
+        );
+        assert!(add_registration(registry, 1).is_err());
+
+        let answers = "[day1]\npart1 = 3\n";
+        assert_eq!(
+            add_answers_stub(answers, 2).unwrap(),
+            "[day1]\npart1 = 3\n\n[day2]\n"
+        );
+        assert!(add_answers_stub(answers, 1).is_err());
+    }
+}
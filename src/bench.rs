@@ -0,0 +1,161 @@
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use crate::{Context, Part, Result, Solution};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub p95: Duration,
+    pub std_dev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Stats {
+        assert!(!samples.is_empty(), "no samples to compute statistics on");
+        let mut nanos: Vec<f64> = samples.iter().map(|d| d.as_nanos() as f64).collect();
+        nanos.sort_by(f64::total_cmp);
+        let n = nanos.len();
+        let median = if n.is_multiple_of(2) {
+            (nanos[n / 2 - 1] + nanos[n / 2]) / 2.0
+        } else {
+            nanos[n / 2]
+        };
+        let mean = nanos.iter().sum::<f64>() / n as f64;
+        let p95 = nanos[((n as f64 * 0.95).ceil() as usize).clamp(1, n) - 1];
+        let variance = nanos.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64;
+        let duration = |nanos: f64| Duration::from_nanos(nanos.round() as u64);
+        Stats {
+            min: duration(nanos[0]),
+            median: duration(median),
+            mean: duration(mean),
+            p95: duration(p95),
+            std_dev: duration(variance.sqrt()),
+        }
+    }
+}
+
+pub struct BenchReport {
+    pub iterations: usize,
+    pub warmup: usize,
+    /// `None` when the input was parsed only once
+    pub parse: Option<Stats>,
+    pub part1: Option<Stats>,
+    pub part2: Option<Stats>,
+}
+
+/// Runs the selected parts `iterations` times after a few warm-up runs.
+///
+/// The input is parsed again before each iteration, unless `reuse_input` is set.
+pub fn bench_solution<'a, S: Solution<'a>>(
+    solution: &S,
+    input: &'a str,
+    ctx: &Context,
+    part: Option<Part>,
+    iterations: usize,
+    reuse_input: bool,
+) -> Result<BenchReport> {
+    let iterations = iterations.max(1);
+    let warmup = (iterations / 10).max(1);
+    let selected = |p| part.is_none_or(|part| part == p);
+    let mut parse_samples = Vec::with_capacity(iterations);
+    let mut part1_samples = Vec::with_capacity(iterations);
+    let mut part2_samples = Vec::with_capacity(iterations);
+    let reused = if reuse_input {
+        Some(solution.parse(input)?)
+    } else {
+        None
+    };
+    for i in 0..(warmup + iterations) {
+        let measured = i >= warmup;
+        let parsed;
+        let parsed = match &reused {
+            Some(parsed) => parsed,
+            None => {
+                let start = Instant::now();
+                parsed = black_box(solution.parse(input)?);
+                if measured {
+                    parse_samples.push(start.elapsed());
+                }
+                &parsed
+            }
+        };
+        if selected(Part::Part1) {
+            let start = Instant::now();
+            black_box(solution.part1(black_box(parsed), ctx)?);
+            if measured {
+                part1_samples.push(start.elapsed());
+            }
+        }
+        if selected(Part::Part2) {
+            let start = Instant::now();
+            black_box(solution.part2(black_box(parsed), ctx)?);
+            if measured {
+                part2_samples.push(start.elapsed());
+            }
+        }
+    }
+    let stats =
+        |samples: Vec<Duration>| (!samples.is_empty()).then(|| Stats::from_samples(&samples));
+    Ok(BenchReport {
+        iterations,
+        warmup,
+        parse: stats(parse_samples),
+        part1: stats(part1_samples),
+        part2: stats(part2_samples),
+    })
+}
+
+pub fn print_bench(report: &BenchReport) {
+    println!(
+        "\nBenchmark ({} iterations after {} warm-up):",
+        report.iterations, report.warmup
+    );
+    println!(
+        "           {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "min", "median", "mean", "p95", "std dev"
+    );
+    let steps = [
+        ("parse", &report.parse),
+        ("part1", &report.part1),
+        ("part2", &report.part2),
+    ];
+    for (name, stats) in steps {
+        if let Some(s) = stats {
+            println!(
+                "    {name}: {:>12} {:>12} {:>12} {:>12} {:>12}",
+                format!("{:.2?}", s.min),
+                format!("{:.2?}", s.median),
+                format!("{:.2?}", s.mean),
+                format!("{:.2?}", s.p95),
+                format!("{:.2?}", s.std_dev),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_bench {
+    use std::time::Duration;
+
+    use crate::bench::Stats;
+
+    #[test]
+    fn test_stats() {
+        let samples = [4, 1, 3, 2, 10].map(Duration::from_micros);
+        assert_eq!(
+            Stats::from_samples(&samples),
+            Stats {
+                min: Duration::from_micros(1),
+                median: Duration::from_micros(3),
+                mean: Duration::from_micros(4),
+                p95: Duration::from_micros(10),
+                std_dev: Duration::from_nanos(3162),
+            }
+        );
+    }
+}
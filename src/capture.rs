@@ -0,0 +1,65 @@
+use std::{cell::RefCell, fmt};
+
+thread_local! {
+    static CAPTURED: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, collecting what it prints with [`day_println!`](crate::day_println) on this thread.
+///
+/// The capture is thread-local: what the threads spawned by `f`, such as rayon workers, print is
+/// not captured and goes straight to stdout, possibly amid the output of other days.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let previous = CAPTURED.with(|c| c.replace(Some(String::new())));
+    let res = f();
+    let output = CAPTURED.with(|c| c.replace(previous)).unwrap_or_default();
+    (res, output)
+}
+
+/// Prints a line to stdout, or to the capture buffer when called within [`capture`]
+pub fn print_line(args: fmt::Arguments) {
+    CAPTURED.with(|c| match c.borrow_mut().as_mut() {
+        Some(output) => {
+            fmt::write(output, args).expect("formatting into a String does not fail");
+            output.push('\n');
+        }
+        None => println!("{args}"),
+    })
+}
+
+/// Prints the lines captured on another thread, as if they were printed on this one
+pub fn print_captured(output: &str) {
+    output
+        .lines()
+        .for_each(|line| print_line(format_args!("{line}")));
+}
+
+/// `println!` for the output of a day that is not an answer, captured when running several days.
+///
+/// Only the lines printed by the thread running the part are captured, see [`capture`].
+#[macro_export]
+macro_rules! day_println {
+    () => {
+        aoc23::capture::print_line(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        aoc23::capture::print_line(format_args!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod test_capture {
+    use crate::capture::{capture, print_line};
+
+    #[test]
+    fn test_capture() {
+        let (res, output) = capture(|| {
+            print_line(format_args!("Found loop from {} to {}", 3, 10));
+            let (_, inner) = capture(|| print_line(format_args!("inner")));
+            assert_eq!(inner, "inner\n");
+            print_line(format_args!(""));
+            42
+        });
+        assert_eq!(res, 42);
+        assert_eq!(output, "Found loop from 3 to 10\n\n");
+    }
+}
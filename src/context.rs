@@ -0,0 +1,183 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use thiserror::Error;
+
+use crate::{raster::Image, visualize::Visualizer, Result};
+
+/// A typed parameter of a puzzle, with the value used for the real input.
+///
+/// Declared as a constant by the day and read with [`Context::param`]:
+/// `const STEPS: Param<usize> = Param::new("steps", 64);`
+pub struct Param<T> {
+    pub name: &'static str,
+    pub default: T,
+}
+
+impl<T> Param<T> {
+    pub const fn new(name: &'static str, default: T) -> Self {
+        Param { name, default }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("invalid value '{value}' for parameter '{name}': {err}")]
+pub struct ParamError {
+    name: &'static str,
+    value: String,
+    err: String,
+}
+
+/// Error of a part that gave up because the runner cancelled it, see [`Context::check_cancelled`]
+#[derive(Error, Debug)]
+#[error("cancelled")]
+pub struct Cancelled;
+
+/// State of a run shared with the parts of a solution
+#[derive(Default, Clone)]
+pub struct Context {
+    params: Vec<(String, String)>,
+    visualizer: Option<Arc<Mutex<dyn Visualizer>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Context {
+    pub fn new(params: &[(String, String)]) -> Self {
+        Context {
+            params: params.to_vec(),
+            ..Context::default()
+        }
+    }
+
+    pub fn with_param(mut self, name: &str, value: impl Display) -> Self {
+        self.params.push((String::from(name), value.to_string()));
+        self
+    }
+
+    pub fn with_visualizer(mut self, visualizer: impl Visualizer + 'static) -> Self {
+        self.visualizer = Some(Arc::new(Mutex::new(visualizer)));
+        self
+    }
+
+    /// Whether the frames are shown, to skip work only needed by the visualization
+    pub fn visualizing(&self) -> bool {
+        self.visualizer.is_some()
+    }
+
+    /// Shows a frame if the run is visualized
+    pub fn frame(&self, frame: impl Display) -> Result<()> {
+        self.with_visualizer_do(|v| v.frame(&frame))
+    }
+
+    /// Shows a frame drawn as an image if the run is visualized, by a backend that can show it
+    pub fn image(&self, image: &Image) -> Result<()> {
+        self.with_visualizer_do(|v| v.image(image))
+    }
+
+    pub(crate) fn begin_visualization(&self) -> Result<()> {
+        self.with_visualizer_do(|v| v.begin())
+    }
+
+    pub(crate) fn end_visualization(&self) -> Result<()> {
+        self.with_visualizer_do(|v| v.end())
+    }
+
+    fn with_visualizer_do(&self, f: impl FnOnce(&mut dyn Visualizer) -> Result<()>) -> Result<()> {
+        match &self.visualizer {
+            Some(visualizer) => f(&mut *visualizer.lock().unwrap_or_else(|e| e.into_inner())),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails once the runner gave up on the part, to be called regularly by the long searches
+    /// so that `--timeout` can stop them
+    pub fn check_cancelled(&self) -> Result<(), Cancelled> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The same context, with its own cancellation
+    pub(crate) fn for_part(&self) -> Context {
+        Context {
+            cancelled: Arc::default(),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns the value given for `param`, or its default; the last value given wins.
+    pub fn param<T>(&self, param: &Param<T>) -> Result<T, ParamError>
+    where
+        T: FromStr + Clone,
+        T::Err: Display,
+    {
+        match self
+            .params
+            .iter()
+            .rev()
+            .find(|(name, _)| name == param.name)
+        {
+            Some((_, value)) => value.parse().map_err(|err: T::Err| ParamError {
+                name: param.name,
+                value: value.clone(),
+                err: err.to_string(),
+            }),
+            None => Ok(param.default.clone()),
+        }
+    }
+}
+
+/// Parses a `key=value` command line parameter
+pub fn parse_key_value(s: &str) -> Result<(String, String)> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((String::from(key.trim()), String::from(value.trim())))
+        }
+        _ => anyhow::bail!("expected a parameter of the form key=value, got '{s}'"),
+    }
+}
+
+#[cfg(test)]
+mod test_context {
+    use crate::context::{parse_key_value, Context, Param};
+
+    const STEPS: Param<usize> = Param::new("steps", 64);
+
+    #[test]
+    fn test_param() {
+        assert_eq!(Context::default().param(&STEPS).unwrap(), 64);
+        let ctx = Context::default().with_param("steps", 6);
+        assert_eq!(ctx.param(&STEPS).unwrap(), 6);
+        let ctx = ctx.with_param("steps", "many");
+        assert_eq!(
+            ctx.param(&STEPS).unwrap_err().to_string(),
+            "invalid value 'many' for parameter 'steps': invalid digit found in string"
+        );
+        assert_eq!(
+            parse_key_value("steps = 6").unwrap(),
+            (String::from("steps"), String::from("6"))
+        );
+        assert!(parse_key_value("steps").is_err());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let ctx = Context::default();
+        let part_ctx = ctx.for_part();
+        part_ctx.clone().cancel();
+        assert!(part_ctx.check_cancelled().is_err());
+        assert!(ctx.check_cancelled().is_ok());
+    }
+}
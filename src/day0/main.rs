@@ -1,17 +1,16 @@
 use aoc23::*;
 
-main!(0, 0);
+// add the example cases once the parts are solved, such as `main!((example, part1, 42));`
+main!();
 
-type Input = &str;
-
-fn parse(input: &str) -> Result<Input> {
+fn parse(input: &str) -> Result<&str> {
     Ok(input)
 }
 
-fn part1(foo: &Input) -> Result<usize> {
-    Ok(42)
+fn part1(_input: &str) -> Result<Answer> {
+    Ok(Answer::Unsolved)
 }
 
-fn part2(foo: &Input) -> Result<usize> {
-    Ok(42)
+fn part2(_input: &str) -> Result<Answer> {
+    Ok(Answer::Unsolved)
 }
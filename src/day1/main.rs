@@ -1,6 +1,10 @@
 use aoc23::*;
 
-main!("example1.txt", 142, "example2.txt", 281);
+main!(
+    (example1, part1, 142),
+    (example1, part2, 142),
+    (example2, part2, 281),
+);
 
 fn parse(input: &str) -> Result<&str> {
     // no parsing do before parts code
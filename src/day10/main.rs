@@ -2,19 +2,34 @@ use std::fmt::Display;
 
 use anyhow::anyhow;
 use aoc23::grid::{ByteGrid, Coord, Grid};
+use aoc23::raster::{Image, Rgb};
 use aoc23::*;
 use colored::{ColoredString, Colorize};
 use itertools::Itertools;
 
-main!("example1.txt", 8, "example2.txt", 4);
+main!(
+    with_context,
+    (example1, part1, 8),
+    (example1, part2, 1),
+    (example2, part1, 23),
+    (example2, part2, 4),
+);
 
 type Input = ByteGrid;
 
+const TILE_SIZE: usize = 3;
+const PIXEL_SIZE: usize = 2;
+const CELL_SIZE: usize = TILE_SIZE * PIXEL_SIZE;
+
+/// Pipes followed, and then tiles scanned, between two images of the animation
+const PIPE_STEPS_PER_FRAME: usize = 15;
+const SCAN_STEPS_PER_FRAME: usize = 30;
+
 fn parse(input: &str) -> Result<Input> {
     Ok(ByteGrid::from_lines(input))
 }
 
-fn part1(maze: &Input) -> Result<usize> {
+fn part1(maze: &Input, _ctx: &Context) -> Result<usize> {
     let start_pos = maze
         .indexed_iter()
         .find(|(_, &tile)| tile == b'S')
@@ -123,6 +138,20 @@ impl InOutStatus {
             s => *s,
         }
     }
+
+    const UNKNOWN_COLOR: Rgb = Rgb(0xA0, 0xA0, 0xA0);
+    const INSIDE_COLOR: Rgb = Rgb(0xFF, 0x20, 0x20);
+    const OUTSIDE_COLOR: Rgb = Rgb(0x40, 0x40, 0xF0);
+    const MAINLOOP_COLOR: Rgb = Rgb(0xD0, 0xD0, 0x40);
+
+    fn get_color(&self) -> Rgb {
+        match *self {
+            InOutStatus::Unknown => Self::UNKNOWN_COLOR,
+            InOutStatus::Inside => Self::INSIDE_COLOR,
+            InOutStatus::Outside => Self::OUTSIDE_COLOR,
+            InOutStatus::MainLoop => Self::MAINLOOP_COLOR,
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -131,7 +160,7 @@ struct Tile {
     status: InOutStatus,
 }
 
-fn part2(maze: &Input) -> Result<usize> {
+fn part2(maze: &Input, ctx: &Context) -> Result<usize> {
     let start_pos = maze
         .indexed_iter()
         .find(|(_, &tile)| tile == b'S')
@@ -146,9 +175,10 @@ fn part2(maze: &Input) -> Result<usize> {
         tile: start_pipe,
         status: InOutStatus::MainLoop,
     };
-    color_main_loop(&mut maze, start_pos);
-    color_inside_outside(&mut maze);
-    println!("{}", MazeDisplayer(&maze));
+    let mut drawing = Drawing::new(&maze, ctx)?;
+    color_main_loop(&mut maze, start_pos, &mut drawing)?;
+    color_inside_outside(&mut maze, &mut drawing)?;
+    ctx.frame(MazeDisplayer(&maze))?;
     Ok(maze
         .lines
         .iter()
@@ -160,7 +190,11 @@ fn part2(maze: &Input) -> Result<usize> {
         .sum())
 }
 
-fn color_main_loop(maze: &mut grid::Grid<Tile>, start_pos: (usize, usize)) {
+fn color_main_loop(
+    maze: &mut grid::Grid<Tile>,
+    start_pos: (usize, usize),
+    drawing: &mut Drawing,
+) -> Result<()> {
     let mut current_pipes = vec![start_pos; 1];
     while !current_pipes.is_empty() {
         let next_pipes = current_pipes
@@ -174,40 +208,129 @@ fn color_main_loop(maze: &mut grid::Grid<Tile>, start_pos: (usize, usize)) {
                 )
             })
             .collect_vec();
-        current_pipes
-            .into_iter()
-            .for_each(|coord| maze[coord].status = InOutStatus::MainLoop);
+        for coord in current_pipes {
+            maze[coord].status = InOutStatus::MainLoop;
+            drawing.draw(coord, &maze[coord]);
+        }
+        drawing.step(PIPE_STEPS_PER_FRAME)?;
         current_pipes = next_pipes;
     }
+    drawing.send()
 }
 
-fn color_inside_outside(maze: &mut grid::Grid<Tile>) {
-    for line in &mut maze.lines {
-        let mut status = InOutStatus::Outside;
-        let mut in_main_loop = false;
-        let mut from_below = false;
-        for tile in line {
+fn color_inside_outside(maze: &mut grid::Grid<Tile>, drawing: &mut Drawing) -> Result<()> {
+    for y in 0..maze.height {
+        let mut scan = Scan::default();
+        for x in 0..maze.width {
+            let tile = &mut maze[(x, y)];
             if tile.status == InOutStatus::MainLoop {
-                if !in_main_loop && !connects_left(&tile.tile) {
-                    in_main_loop = connects_right(&tile.tile);
-                    if in_main_loop {
-                        from_below = connects_down(&tile.tile);
-                    } else {
-                        status = status.flipped();
-                    }
-                } else if in_main_loop && !connects_right(&tile.tile) {
-                    in_main_loop = false;
-                    if from_below != connects_down(&tile.tile) {
-                        status = status.flipped();
-                    }
-                }
+                scan.cross(tile.tile);
             } else {
-                tile.status = status;
+                tile.status = scan.status;
             }
+            drawing.draw((x, y), tile);
+            drawing.step(SCAN_STEPS_PER_FRAME)?;
+        }
+    }
+    drawing.send()
+}
+
+/// State of the scan of a row, from left to right
+struct Scan {
+    status: InOutStatus,
+    in_main_loop: bool,
+    from_below: bool,
+}
+
+impl Default for Scan {
+    fn default() -> Self {
+        Scan {
+            status: InOutStatus::Outside,
+            in_main_loop: false,
+            from_below: false,
         }
     }
 }
 
+impl Scan {
+    fn cross(&mut self, pipe: u8) {
+        if !self.in_main_loop && !connects_left(&pipe) {
+            self.in_main_loop = connects_right(&pipe);
+            if self.in_main_loop {
+                self.from_below = connects_down(&pipe);
+            } else {
+                self.status = self.status.flipped();
+            }
+        } else if self.in_main_loop && !connects_right(&pipe) {
+            self.in_main_loop = false;
+            if self.from_below != connects_down(&pipe) {
+                self.status = self.status.flipped();
+            }
+        }
+    }
+}
+
+/// Image of the maze, only drawn when visualizing, and sent to the visualizer every few steps
+struct Drawing<'a> {
+    ctx: &'a Context,
+    image: Option<Image>,
+    steps: usize,
+}
+
+impl<'a> Drawing<'a> {
+    fn new(maze: &Grid<Tile>, ctx: &'a Context) -> Result<Self> {
+        let drawing = Drawing {
+            ctx,
+            image: ctx
+                .visualizing()
+                .then(|| Image::from_grid_with(maze, CELL_SIZE, tile_pixel)),
+            steps: 0,
+        };
+        drawing.send()?;
+        Ok(drawing)
+    }
+
+    fn draw(&mut self, coord: Coord, tile: &Tile) {
+        if let Some(image) = &mut self.image {
+            image.draw_cell(coord, CELL_SIZE, |pixel| tile_pixel(tile, pixel));
+        }
+    }
+
+    /// Sends the image once every `steps_per_frame` steps
+    fn step(&mut self, steps_per_frame: usize) -> Result<()> {
+        self.steps += 1;
+        if self.steps.is_multiple_of(steps_per_frame) {
+            self.send()?;
+        }
+        Ok(())
+    }
+
+    fn send(&self) -> Result<()> {
+        match &self.image {
+            Some(image) => self.ctx.image(image),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Draws the pipe as a 3x3 glyph, in the color of its status
+fn tile_pixel(tile: &Tile, (dx, dy): (usize, usize)) -> Rgb {
+    let glyph: &[(usize, usize)] = match tile.tile {
+        b'|' => &[(1, 0), (1, 1), (1, 2)],
+        b'J' => &[(1, 0), (1, 1), (0, 1)],
+        b'L' => &[(1, 0), (1, 1), (2, 1)],
+        b'7' => &[(0, 1), (1, 1), (1, 2)],
+        b'F' => &[(2, 1), (1, 1), (1, 2)],
+        b'-' => &[(0, 1), (1, 1), (2, 1)],
+        _ => &[],
+    };
+    if glyph.contains(&(dx / PIXEL_SIZE, dy / PIXEL_SIZE)) {
+        tile.status.get_color()
+    } else {
+        Rgb::BLACK
+    }
+}
+
 struct MazeDisplayer<'a>(&'a Grid<Tile>);
 
 impl<'a> Display for MazeDisplayer<'a> {
@@ -4,7 +4,15 @@ use aoc23::{
 };
 use itertools::Itertools;
 
-main!(374, 82000210);
+main!(
+    with_context,
+    (example, part1, 374),
+    (example, part2, 1030, expansion_factor = 10),
+    (example, part2, 8410, expansion_factor = 100),
+    (example, part2, 82000210),
+);
+
+const EXPANSION_FACTOR: Param<usize> = Param::new("expansion_factor", 1000000);
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 enum Tile {
@@ -12,12 +20,14 @@ enum Tile {
     Galaxy,
 }
 
-impl From<u8> for Tile {
-    fn from(value: u8) -> Self {
+impl TryFrom<u8> for Tile {
+    type Error = &'static str;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            b'.' => Tile::Empty,
-            b'#' => Tile::Galaxy,
-            _ => panic!("bad character"),
+            b'.' => Ok(Tile::Empty),
+            b'#' => Ok(Tile::Galaxy),
+            _ => Err("expected '.' or '#'"),
         }
     }
 }
@@ -25,15 +35,15 @@ impl From<u8> for Tile {
 type Space = Grid<Tile>;
 
 fn parse(input: &str) -> Result<Space> {
-    Ok(Space::from_lines_mapped(input, |b| b.into()))
+    Space::try_from_lines(input)
 }
 
-fn part1(space: &Space) -> Result<usize> {
+fn part1(space: &Space, _ctx: &Context) -> Result<usize> {
     cosmic_expansion(space, 2)
 }
 
-fn part2(space: &Space) -> Result<usize> {
-    cosmic_expansion(space, 1000000)
+fn part2(space: &Space, ctx: &Context) -> Result<usize> {
+    cosmic_expansion(space, ctx.param(&EXPANSION_FACTOR)?)
 }
 
 fn cosmic_expansion(space: &Space, expansion_factor: usize) -> Result<usize> {
@@ -1,49 +1,59 @@
+use std::borrow::Cow;
+
 use aoc23::*;
 
-main!(21, 525152);
+main!(with_context, (example, part1, 21), (example, part2, 525152));
 
-#[apply(parse_ordered!)]
+#[derive(Debug, Default, AocParse)]
 #[delim(' ')]
-#[derive(Debug, Default)]
-struct Row {
+struct Row<'a> {
+    /// Borrowed from the input, except when unfolded
     #[parse()]
-    row: String,
+    row: Cow<'a, str>,
     #[parse(collect(','))]
     groups: Vec<usize>,
 }
 
-type Springs = Vec<Row>;
+type Springs<'a> = Vec<Row<'a>>;
 
-fn parse(input: &str) -> Result<Springs> {
-    collect_lines(input)
+fn parse(input: &str) -> Result<Springs<'_>> {
+    collect_lines_borrowed(input)
 }
 
-fn part1(springs: &Springs) -> Result<usize> {
-    Ok(springs.iter().map(Row::count_arrangements).sum())
+fn part1(springs: &Springs, ctx: &Context) -> Result<usize> {
+    springs.iter().map(|row| row.count_arrangements(ctx)).sum()
 }
 
-fn part2(springs: &Springs) -> Result<usize> {
-    Ok(springs
+fn part2(springs: &Springs, ctx: &Context) -> Result<usize> {
+    springs
         .iter()
         .map(|row| row.unfold())
-        .map(|row| row.count_arrangements())
-        .sum())
+        .map(|row| row.count_arrangements(ctx))
+        .sum()
 }
 
-impl Row {
-    pub fn count_arrangements(&self) -> usize {
-        Self::arrangements(self.row.as_bytes(), 0, &self.groups)
+impl Row<'_> {
+    /// Fails when the part is cancelled, which is checked at each step of the recursion as a
+    /// single row of part 2 can take a long time
+    pub fn count_arrangements(&self, ctx: &Context) -> Result<usize> {
+        Self::arrangements(self.row.as_bytes(), 0, &self.groups, ctx)
     }
 
-    fn arrangements(row: &[u8], current_group_length: usize, groups: &[usize]) -> usize {
+    fn arrangements(
+        row: &[u8],
+        current_group_length: usize,
+        groups: &[usize],
+        ctx: &Context,
+    ) -> Result<usize> {
+        ctx.check_cancelled()?;
         if row.is_empty() {
-            if groups.is_empty() && current_group_length == 0 {
-                return 1;
-            } else if groups.len() == 1 && current_group_length == groups[0] {
-                return 1;
+            if (groups.is_empty() && current_group_length == 0)
+                || (groups.len() == 1 && current_group_length == groups[0])
+            {
+                return Ok(1);
             } else {
                 // invalid arrangement
-                return 0;
+                return Ok(0);
             }
         }
         let mut count = 0;
@@ -54,12 +64,12 @@ impl Row {
                 // invalid arrangement
                 0
             } else if current_group_length == 0 {
-                Self::arrangements(&row[1..], 0, groups)
+                Self::arrangements(&row[1..], 0, groups, ctx)?
             } else if groups.is_empty() {
                 // invalid arrangement: no enough groups
                 0
             } else {
-                Self::arrangements(&row[1..], 0, &groups[1..])
+                Self::arrangements(&row[1..], 0, &groups[1..], ctx)?
             }
         } else {
             0
@@ -69,22 +79,22 @@ impl Row {
                 0
             } else if current_group_length == 0 {
                 // start a new group
-                Self::arrangements(&row[1..], 1, groups)
+                Self::arrangements(&row[1..], 1, groups, ctx)?
             } else if current_group_length + 1 > groups[0] {
                 // group is too big
                 0
             } else {
-                Self::arrangements(&row[1..], current_group_length + 1, groups)
+                Self::arrangements(&row[1..], current_group_length + 1, groups, ctx)?
             }
         } else {
             0
         };
-        count
+        Ok(count)
     }
 
     fn unfold(&self) -> Self {
         Row {
-            row: vec![self.row.clone(); 5].join("?"),
+            row: Cow::Owned([self.row.as_ref(); 5].join("?")),
             groups: self.groups.repeat(5),
         }
     }
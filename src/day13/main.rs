@@ -3,18 +3,13 @@ use itertools::Itertools;
 
 main!(405, 400);
 
-#[cfg(windows)]
-const TWO_LINES: &str = "\r\n\r\n";
-#[cfg(not(windows))]
-const TWO_LINES: &str = "\n\n";
-
 type Pattern = Grid<bool>;
 
 type Patterns = Vec<Pattern>;
 
 fn parse(input: &str) -> Result<Patterns> {
     Ok(input
-        .split(TWO_LINES)
+        .split("\n\n")
         .filter(|s| !s.is_empty())
         .map(|s| Pattern::from_lines_mapped(s, |byte| byte == b'#'))
         .collect_vec())
@@ -6,7 +6,16 @@ use aoc23::{
 };
 use colored::Colorize;
 
-main!(136, 64);
+main!(
+    with_context,
+    (example, part1, 136),
+    (example, part2, 64),
+    // before and within the first turn of the loop, which is found from 3 to 10
+    (example, part2, 87, cycles = 1),
+    (example, part2, 65, cycles = 5),
+);
+
+const CYCLES: Param<usize> = Param::new("cycles", 1000000000);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Rock {
@@ -18,51 +27,55 @@ enum Rock {
 type Rocks = Grid<Rock>;
 
 fn parse(input: &str) -> Result<Rocks> {
-    Ok(Rocks::from_lines(input))
+    Rocks::try_from_lines(input)
 }
 
-fn part1(rocks: &Rocks) -> Result<usize> {
+fn part1(rocks: &Rocks, ctx: &Context) -> Result<usize> {
     let mut rocks = rocks.clone();
     tilt(&mut rocks, Dir::North);
+    ctx.frame(RockDisplay(&rocks))?;
     Ok(rocks
         .indexed_iter()
         .map(|(coord, _)| north_load(&rocks, coord))
         .sum())
 }
 
-fn part2(rocks: &Rocks) -> Result<usize> {
+fn part2(rocks: &Rocks, ctx: &Context) -> Result<usize> {
+    let cycles = ctx.param(&CYCLES)?;
     let mut rocks = rocks.clone();
     let mut history = HashMap::new();
 
     let mut n = 0;
-    while history.entry(uuid(&rocks)).or_insert(n) == &n {
+    while n < cycles {
+        if let Some(loop_start) = history.insert(uuid(&rocks), n) {
+            day_println!("Found loop from {loop_start} to {n}");
+            // the remaining cycles only change the rocks by their last partial turn of the loop
+            let pos_in_loop = (cycles - n) % (n - loop_start);
+            for _ in 0..pos_in_loop {
+                spin_cycle(&mut rocks);
+            }
+            break;
+        }
         spin_cycle(&mut rocks);
+        ctx.frame(RockDisplay(&rocks))?;
         n += 1;
     }
 
-    let loop_start = history.get(&uuid(&rocks)).unwrap();
-    let loop_end = n;
-    println!("Found loop from {loop_start} to {loop_end}");
-
-    let pos_in_loop = (1000000000 - loop_start) % (loop_end - loop_start);
-
-    for _ in 0..pos_in_loop {
-        spin_cycle(&mut rocks);
-    }
-
     Ok(rocks
         .indexed_iter()
         .map(|(coord, _)| north_load(&rocks, coord))
         .sum())
 }
 
-impl From<u8> for Rock {
-    fn from(value: u8) -> Self {
+impl TryFrom<u8> for Rock {
+    type Error = &'static str;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            b'.' => Rock::Empty,
-            b'#' => Rock::Square,
-            b'O' => Rock::Round,
-            _ => panic!("expected rock shape"),
+            b'.' => Ok(Rock::Empty),
+            b'#' => Ok(Rock::Square),
+            b'O' => Ok(Rock::Round),
+            _ => Err("expected rock shape"),
         }
     }
 }
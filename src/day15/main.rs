@@ -4,7 +4,7 @@ main!(1320, 145);
 
 type InitSeq<'a> = Vec<&'a str>;
 
-fn parse(input: &str) -> Result<InitSeq> {
+fn parse(input: &str) -> Result<InitSeq<'_>> {
     Ok(input.trim().split(',').collect())
 }
 
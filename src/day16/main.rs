@@ -9,7 +9,7 @@ use bitflags::bitflags;
 use colored::Colorize;
 use rayon::prelude::*;
 
-main!(46, 51);
+main!(with_context, (example, part1, 46), (example, part2, 51));
 
 bitflags! {
     #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -34,15 +34,16 @@ fn parse(input: &str) -> Result<Cave> {
     Ok(Cave::from_lines(input))
 }
 
-fn part1(cave: &Cave) -> Result<usize> {
+fn part1(cave: &Cave, ctx: &Context) -> Result<usize> {
     let mut cave = cave.clone();
 
     add_beam(&mut cave, (0, 0), BeamDir::Right);
+    ctx.frame(CaveDisplay(&cave))?;
 
     Ok(energized_tiles(&mut cave))
 }
 
-fn part2(cave: &Cave) -> Result<usize> {
+fn part2(cave: &Cave, _ctx: &Context) -> Result<usize> {
     let all_starting_points = ((0..cave.width).map(|x| ((x, 0), BeamDir::Down)))
         .chain((0..cave.height).map(|y| ((0, y), BeamDir::Right)))
         .chain((0..cave.width).map(|x| ((x, cave.height - 1), BeamDir::Up)))
@@ -64,7 +64,7 @@ fn find_path(map: &Map, min_straight: u8, max_straight: u8) -> Result<u32> {
         coord: (0, 0),
         dir: Dir::Up,
     });
-    while !graph.peek().is_some_and(|node| node.coord == destination) {
+    while graph.peek().is_none_or(|node| node.coord != destination) {
         let node = graph.pop().expect("I'm lost");
         if node.distance > map[node.coord].min_distance[node.dir as usize] {
             continue;
@@ -1,5 +1,7 @@
-use anyhow::anyhow;
-use aoc23::*;
+use aoc23::{
+    parse::{bracketed, int, lines, one_of, tag, take, Parser},
+    *,
+};
 use itertools::Itertools;
 
 main!(62, 952408144115);
@@ -22,34 +24,36 @@ struct Instr {
 type DigPlan = (Vec<Instr>, Vec<Instr>);
 
 fn parse(input: &str) -> Result<DigPlan> {
-    let instructions1 = input
-        .lines()
-        .map(|l| -> Result<Instr> {
-            let mut split = l.split(' ');
-            Ok(Instr {
-                dir: split.next().ok_or(anyhow!("parse failed"))?.parse()?,
-                count: split.next().ok_or(anyhow!("parse failed"))?.parse()?,
-            })
-        })
-        .try_collect()?;
-    let instructions2 = input
-        .lines()
-        .map(|l| -> Result<Instr> {
-            let hexa = l.split_once('#').ok_or(anyhow!("parse failed"))?.1;
-            Ok(Instr {
-                dir: match hexa.chars().nth(5) {
-                    Some('0') => Dir::Right,
-                    Some('1') => Dir::Down,
-                    Some('2') => Dir::Left,
-                    Some('3') => Dir::Up,
-                    _ => Err(anyhow!("parse failed"))?,
+    let hexa = take(5).try_map(|digits| isize::from_str_radix(digits, 16));
+    let instructions = dir(["R", "D", "L", "U"])
+        .skip(tag(" "))
+        .then(int())
+        .skip(tag(" "))
+        .then(bracketed("(#", hexa.then(dir(["0", "1", "2", "3"])), ")"));
+    let instructions: Vec<_> = lines(instructions).parse_all(input)?;
+    Ok(instructions
+        .into_iter()
+        .map(|((dir, count), (hexa_count, hexa_dir))| {
+            (
+                Instr { dir, count },
+                Instr {
+                    dir: hexa_dir,
+                    count: hexa_count,
                 },
-                count: isize::from_str_radix(&hexa[0..5], 16)?,
-            })
+            )
         })
-        .try_collect()?;
+        .unzip())
+}
 
-    Ok((instructions1, instructions2))
+/// A direction written with one of `codes`, in the order right, down, left, up
+fn dir<'a>(codes: [&'static str; 4]) -> impl Parser<'a, Dir> {
+    let [right, down, left, up] = codes;
+    one_of([
+        (right, Dir::Right),
+        (down, Dir::Down),
+        (left, Dir::Left),
+        (up, Dir::Up),
+    ])
 }
 
 fn part1((instructions, _): &DigPlan) -> Result<usize> {
@@ -90,17 +94,3 @@ fn area(polygon: &[(isize, isize)]) -> usize {
         + distance((polygon.first().unwrap(), polygon.last().unwrap()));
     inside_area + perimeter / 2 + 1
 }
-
-impl FromStr for Dir {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.chars().next() {
-            Some('U') => Ok(Dir::Up),
-            Some('D') => Ok(Dir::Down),
-            Some('L') => Ok(Dir::Left),
-            Some('R') => Ok(Dir::Right),
-            _ => Err(anyhow!("failed to parse direction")),
-        }
-    }
-}
@@ -1,19 +1,12 @@
 use std::{collections::HashMap, ops::Range};
 
-use anyhow::anyhow;
 use aoc23::*;
 use itertools::Itertools;
 
 main!(19114, 167409079868000);
 
-#[cfg(windows)]
-const TWO_LINES: &str = "\r\n\r\n";
-#[cfg(not(windows))]
-const TWO_LINES: &str = "\n\n";
-
-#[apply(parse_ordered!)]
-#[delim(TWO_LINES)]
-#[derive(Default)]
+#[derive(Default, AocParse)]
+#[delim("\n\n")]
 struct XMASSystem {
     #[parse()]
     workflows: Workflows,
@@ -33,7 +26,7 @@ impl FromStr for Workflows {
                 .map(|line| -> Result<_> {
                     let (name, rules) = line
                         .split_once('{')
-                        .ok_or(anyhow!("workflow parse error"))?;
+                        .ok_or(parse_error(line, "expected '{' after the workflow name"))?;
                     Ok((
                         String::from(name),
                         parse_collect(&rules[0..rules.len() - 1], ',')?,
@@ -44,74 +37,73 @@ impl FromStr for Workflows {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(AocParse)]
+#[delim(':')]
+struct Rule {
+    #[parse(default = Condition::ALWAYS)]
+    cond: Condition,
+    #[parse()]
+    dest: String,
+}
+
+#[derive(AocParse)]
+#[parse(re("^(?<category>[a-z])(?<comp>[<>])(?<threshold>[0-9]+)$"))]
+struct Condition {
+    category: Category,
+    comp: Comparison,
+    threshold: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AocParse)]
+enum Category {
+    #[parse(prefix = "x")]
+    X,
+    #[parse(prefix = "m")]
+    M,
+    #[parse(prefix = "a")]
+    A,
+    #[parse(prefix = "s")]
+    S,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AocParse)]
 enum Comparison {
+    #[parse(prefix = "<")]
     LessThan,
+    #[parse(prefix = ">")]
     GreaterThan,
 }
 
-struct Rule {
-    which: u8,
-    comp: Comparison,
-    threshold: usize,
-    dest: String,
+impl Condition {
+    /// x > 0, the condition of the last rule of a workflow
+    const ALWAYS: Condition = Condition {
+        category: Category::X,
+        comp: Comparison::GreaterThan,
+        threshold: 0,
+    };
 }
+
 impl Rule {
     fn check(&self, part: &Part) -> bool {
-        let value = match self.which {
-            b'x' => part.x,
-            b'm' => part.m,
-            b'a' => part.a,
-            b's' => part.s,
-            _ => panic!("unknown part category"),
+        let value = match self.cond.category {
+            Category::X => part.x,
+            Category::M => part.m,
+            Category::A => part.a,
+            Category::S => part.s,
         };
-        match self.comp {
-            Comparison::LessThan => value < self.threshold,
-            Comparison::GreaterThan => value > self.threshold,
-        }
-    }
-}
-
-impl FromStr for Rule {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((check, dest)) = s.split_once(':') {
-            let threshold: usize = check[2..].parse()?;
-            let comparison = check.as_bytes()[1];
-            Ok(Rule {
-                which: check.as_bytes()[0],
-                comp: if comparison == b'<' {
-                    Comparison::LessThan
-                } else {
-                    Comparison::GreaterThan
-                },
-                threshold,
-                dest: String::from(dest),
-            })
-        } else {
-            // x > 0 always true
-            Ok(Rule {
-                which: b'x',
-                comp: Comparison::GreaterThan,
-                threshold: 0,
-                dest: String::from(s),
-            })
+        match self.cond.comp {
+            Comparison::LessThan => value < self.cond.threshold,
+            Comparison::GreaterThan => value > self.cond.threshold,
         }
     }
 }
 
-#[apply(parse_ordered!)]
-#[delim(',')]
-#[derive(Default)]
+#[derive(Default, AocParse)]
+#[parse(re(r"^\{x=(?<x>[0-9]+),m=(?<m>[0-9]+),a=(?<a>[0-9]+),s=(?<s>[0-9]+)\}$"))]
 struct Part {
-    #[parse(re("\\{x=([0-9]+)"))]
     x: usize,
-    #[parse(re("m=([0-9]+)"))]
     m: usize,
-    #[parse(re("a=([0-9]+)"))]
     a: usize,
-    #[parse(re("s=([0-9]+)}"))]
     s: usize,
 }
 
@@ -191,25 +183,23 @@ impl Rule {
     fn check_range(&self, range: &XMASRange) -> (XMASRange, XMASRange) {
         let mut included_range = range.clone();
         let mut excluded_range = range.clone();
-        let (range_to_include, range_to_exclude) = match self.which {
-            b'x' => (&mut included_range.0, &mut excluded_range.0),
-            b'm' => (&mut included_range.1, &mut excluded_range.1),
-            b'a' => (&mut included_range.2, &mut excluded_range.2),
-            b's' => (&mut included_range.3, &mut excluded_range.3),
-            _ => panic!("unknown part category"),
+        let (range_to_include, range_to_exclude) = match self.cond.category {
+            Category::X => (&mut included_range.0, &mut excluded_range.0),
+            Category::M => (&mut included_range.1, &mut excluded_range.1),
+            Category::A => (&mut included_range.2, &mut excluded_range.2),
+            Category::S => (&mut included_range.3, &mut excluded_range.3),
         };
-        match self.comp {
+        let threshold = self.cond.threshold;
+        match self.cond.comp {
             Comparison::LessThan => {
-                *range_to_include =
-                    range_to_include.start..self.threshold.min(range_to_include.end);
-                *range_to_exclude =
-                    (self.threshold.max(range_to_exclude.start))..range_to_exclude.end;
+                *range_to_include = range_to_include.start..threshold.min(range_to_include.end);
+                *range_to_exclude = (threshold.max(range_to_exclude.start))..range_to_exclude.end;
             }
             Comparison::GreaterThan => {
                 *range_to_include =
-                    ((self.threshold + 1).max(range_to_include.start))..range_to_include.end;
+                    ((threshold + 1).max(range_to_include.start))..range_to_include.end;
                 *range_to_exclude =
-                    range_to_exclude.start..((self.threshold + 1).min(range_to_exclude.end));
+                    range_to_exclude.start..((threshold + 1).min(range_to_exclude.end));
             }
         }
         (included_range, excluded_range)
@@ -1,5 +1,4 @@
 use aoc23::*;
-use std::str::FromStr;
 
 main!(8, 2286);
 
@@ -23,19 +22,17 @@ fn part2(games: &[Game]) -> Result<usize> {
     Ok(games.iter().map(Game::minimal_power).sum())
 }
 
-#[apply(parse_ordered!)]
-#[delim(':')]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, AocParse, AocDisplay)]
+#[delim(':', write = ": ")]
 struct Game {
     #[parse(re("Game ([0-9]+)"))]
     id: usize,
-    #[parse(collect(';'))]
+    #[parse(collect(';', write = "; "))]
     cubes_sets: Vec<Cubes>,
 }
 
-#[apply(parse_matching!)]
-#[delim(',')]
-#[derive(Default, Debug)]
+#[derive(Default, Debug, PartialEq, AocParse, AocDisplay)]
+#[delim(',', write = ", ")]
 struct Cubes {
     #[parse("([0-9]+) red")]
     red: usize,
@@ -74,3 +71,21 @@ impl Cubes {
         self.red * self.green * self.blue
     }
 }
+
+crate::day_tests! {
+    mod test_display {
+        use super::*;
+
+        #[test]
+        fn test_round_trip() {
+            aoc23::fields::assert_round_trip(parse(INPUT).unwrap());
+        }
+
+        #[test]
+        fn test_puzzle_format() {
+            // the colors in the order of the fields, as they are written back
+            let line = "Game 3: 20 red, 8 green, 6 blue; 4 red, 13 green, 5 blue; 1 red, 5 green";
+            assert_eq!(Game::from_str(line).unwrap().to_string(), line);
+        }
+    }
+}
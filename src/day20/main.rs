@@ -1,30 +1,25 @@
 use std::{
     collections::{hash_map::Entry, HashMap, VecDeque},
     fmt::Display,
-    thread::sleep,
-    time::Duration,
 };
 
-use anyhow::anyhow;
 use aoc23::*;
 use colored::{ColoredString, Colorize};
-use crossterm_cursor::cursor;
-use itertools::Itertools;
 
-main!();
+main!(with_context);
 
-const DISPLAY: bool = false;
+/// Number of button presses animated when visualizing part 2
+const VISUALIZED_PRESSES: Param<usize> = Param::new("visualized_presses", 1000);
 
 type ModuleConf<'a> = HashMap<&'a str, Module<'a>>;
 
-fn parse(input: &str) -> Result<ModuleConf> {
+fn parse(input: &str) -> Result<ModuleConf<'_>> {
+    let lines: Vec<ModuleLine> = collect_lines_borrowed(input)?;
     let mut module_inputs: HashMap<&str, Vec<&str>> = HashMap::new();
-    let mut module_conf: ModuleConf = input
-        .lines()
-        .map(|l| -> Result<_> {
-            let (name, dest_list) = l.split_once("->").ok_or(anyhow!("dests parse error"))?;
-            let name = name.trim_start_matches(['%', '&']).trim_end();
-            let dests = dest_list.split(',').map(|name| name.trim()).collect_vec();
+    let mut module_conf: ModuleConf = lines
+        .into_iter()
+        .map(|ModuleLine { decl, dests }| {
+            let name = decl.name();
             for d in &dests {
                 match module_inputs.entry(d) {
                     Entry::Occupied(mut entry) => {
@@ -35,22 +30,22 @@ fn parse(input: &str) -> Result<ModuleConf> {
                     }
                 }
             }
-            let module = match l.chars().next() {
-                Some('%') => Module::FlipFlop(FlipFlop {
+            let module = match decl {
+                Declaration::FlipFlop(name) => Module::FlipFlop(FlipFlop {
                     name,
                     mem: false,
                     dests,
                 }),
-                Some('&') => Module::Conjunction(Conjunction {
+                Declaration::Conjunction(name) => Module::Conjunction(Conjunction {
                     name,
                     mem: Default::default(),
                     dests,
                 }),
-                _ => Module::Broadcast(Broadcast { name, dests }),
+                Declaration::Broadcast(name) => Module::Broadcast(Broadcast { name, dests }),
             };
-            Ok((name, module))
+            (name, module)
         })
-        .try_collect()?;
+        .collect();
     for (module, inputs) in module_inputs {
         if let Some(Module::Conjunction(conj)) = module_conf.get_mut(module) {
             conj.mem = inputs.into_iter().map(|n| (n, false)).collect()
@@ -59,6 +54,37 @@ fn parse(input: &str) -> Result<ModuleConf> {
     Ok(module_conf)
 }
 
+/// A line of the configuration, as `%a -> b, c`
+#[derive(AocParse)]
+#[delim(" -> ")]
+struct ModuleLine<'a> {
+    #[parse()]
+    decl: Declaration<'a>,
+    #[parse(collect(", "))]
+    dests: Vec<&'a str>,
+}
+
+/// The kind and name of a module
+#[derive(Debug, Clone, Copy, AocParse)]
+enum Declaration<'a> {
+    #[parse(prefix = "%")]
+    FlipFlop(&'a str),
+    #[parse(prefix = "&")]
+    Conjunction(&'a str),
+    #[parse(re("^(broadcaster)$"))]
+    Broadcast(&'a str),
+}
+
+impl<'a> Declaration<'a> {
+    fn name(self) -> &'a str {
+        match self {
+            Declaration::FlipFlop(name)
+            | Declaration::Conjunction(name)
+            | Declaration::Broadcast(name) => name,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct FlipFlop<'a> {
     name: &'a str,
@@ -165,7 +191,7 @@ impl<'a> Broadcast<'a> {
     }
 }
 
-fn part1(module_conf: &ModuleConf) -> Result<usize> {
+fn part1(module_conf: &ModuleConf, _ctx: &Context) -> Result<usize> {
     let mut module_conf = module_conf.clone();
     let (mut high_count, mut low_count) = (0, 0);
     for _ in 0..1000 {
@@ -219,7 +245,7 @@ struct Process<'a> {
     output: &'a str,
 }
 
-fn part2(graph: &ModuleConf) -> Result<usize> {
+fn part2(graph: &ModuleConf, ctx: &Context) -> Result<usize> {
     // Find the "adders" : groups of flipflops than output to the same conjunction
     let adders: Vec<Adder> = graph["broadcaster"]
         .dests()
@@ -237,7 +263,13 @@ fn part2(graph: &ModuleConf) -> Result<usize> {
         })
         .collect();
 
-    if DISPLAY {
+    let presses = adders
+        .iter()
+        .map(|adder| adder.max_val(graph))
+        .reduce(num::integer::lcm)
+        .unwrap();
+
+    if ctx.visualizing() {
         let synchronizer = find_conjunction_dest(adders[0].inverter, graph).unwrap();
         let mut process = Process {
             graph: graph.clone(),
@@ -245,21 +277,13 @@ fn part2(graph: &ModuleConf) -> Result<usize> {
             synchronizer,
             output: "rx",
         };
-        let mut cursor = cursor();
-        println!("{process}");
-        loop {
-            sleep(Duration::from_millis(10));
+        ctx.frame(&process)?;
+        for _ in 0..ctx.param(&VISUALIZED_PRESSES)? {
             push_the_button(&mut process.graph);
-            cursor.move_up(26)?;
-            println!("{process}");
+            ctx.frame(&process)?;
         }
-    } else {
-        Ok(adders
-            .iter()
-            .map(|adder| adder.max_val(graph))
-            .reduce(num::integer::lcm)
-            .unwrap())
     }
+    Ok(presses)
 }
 
 fn find_conjunction_dest<'a>(source: &str, graph: &'a ModuleConf) -> Option<&'a str> {
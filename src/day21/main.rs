@@ -4,7 +4,9 @@ use aoc23::*;
 
 use aoc23::grid::{Coord, Grid};
 
-main!();
+main!(with_context, (example, part1, 16, steps = 6));
+
+const PART1_STEPS: Param<usize> = Param::new("steps", 64);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum TileType {
@@ -31,8 +33,8 @@ fn parse(input: &str) -> Result<Land> {
     }))
 }
 
-fn part1(land: &Land) -> Result<usize> {
-    let steps = if land.width <= 12 { 6 } else { 64 };
+fn part1(land: &Land, ctx: &Context) -> Result<usize> {
+    let steps = ctx.param(&PART1_STEPS)?;
     let mut current = land.clone();
     let mut next = land.clone();
     for _ in 0..steps {
@@ -70,7 +72,7 @@ const MAP_SIZE: usize = 131;
 const NB_FULL_MAPS: usize = (STEPS - MAP_SIZE / 2) / MAP_SIZE; // 202 300
 
 // mostly solved geometrically, see day21.png 😄
-fn part2(land: &Land) -> Result<usize> {
+fn part2(land: &Land, _ctx: &Context) -> Result<usize> {
     let mut map = land.mapped(|tile| tile.tile_type);
     block_unreachable_spots(&mut map);
     let full_even = count_garden_plots(&map, true, |_| true);
@@ -34,9 +34,8 @@ fn part2(bricks: &Vec<Brick>) -> Result<usize> {
     Ok(chain_reaction_potential(&graph))
 }
 
-#[apply(parse_ordered!)]
+#[derive(Debug, Default, Clone, PartialEq, AocParse, AocDisplay)]
 #[delim(',')]
-#[derive(Debug, Default, Clone)]
 struct Coord {
     #[parse()]
     x: usize,
@@ -46,9 +45,8 @@ struct Coord {
     z: usize,
 }
 
-#[apply(parse_ordered!)]
+#[derive(Debug, Default, Clone, PartialEq, AocParse, AocDisplay)]
 #[delim('~')]
-#[derive(Debug, Default, Clone)]
 struct Brick {
     #[parse()]
     start: Coord,
@@ -95,7 +93,7 @@ impl Brick {
     }
 }
 
-fn fall(bricks: &mut Vec<Brick>) {
+fn fall(bricks: &mut [Brick]) {
     for i in 0..bricks.len() {
         let z = bricks[i]
             .bottom_cubes()
@@ -189,3 +187,14 @@ fn get_nb_falling_bricks(brick_idx: usize, graph: &HashMap<usize, SupportNode>)
     }
     removed.len() - 1
 }
+
+crate::day_tests! {
+    mod test_display {
+        use super::*;
+
+        #[test]
+        fn test_round_trip() {
+            aoc23::fields::assert_round_trip(parse(INPUT).unwrap());
+        }
+    }
+}
@@ -10,7 +10,7 @@ use aoc23::{
 };
 use itertools::Itertools;
 
-main!(94, 154);
+main!(with_context, (example, part1, 94), (example, part2, 154));
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 enum Tile {
@@ -22,26 +22,34 @@ enum Tile {
 type Trail = Grid<Tile>;
 
 fn parse(input: &str) -> Result<Trail> {
-    Ok(Trail::from_lines_mapped(input, |b| match b {
-        b'.' => Tile::Path,
-        b'#' => Tile::Forest,
-        b'^' => Tile::Slope(Dir::Up),
-        b'v' => Tile::Slope(Dir::Down),
-        b'>' => Tile::Slope(Dir::Right),
-        b'<' => Tile::Slope(Dir::Left),
-        _ => panic!("trail parse error"),
-    }))
+    Trail::try_from_lines(input)
 }
 
-fn part1(trail: &Trail) -> Result<usize> {
+impl TryFrom<u8> for Tile {
+    type Error = &'static str;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'.' => Ok(Tile::Path),
+            b'#' => Ok(Tile::Forest),
+            b'^' => Ok(Tile::Slope(Dir::Up)),
+            b'v' => Ok(Tile::Slope(Dir::Down)),
+            b'>' => Ok(Tile::Slope(Dir::Right)),
+            b'<' => Ok(Tile::Slope(Dir::Left)),
+            _ => Err("expected a trail tile"),
+        }
+    }
+}
+
+fn part1(trail: &Trail, _ctx: &Context) -> Result<usize> {
     let mut graph = Graph::from(trail, true);
     Ok(graph.longest_icy_hike())
 }
 
-fn part2(trail: &Trail) -> Result<usize> {
+fn part2(trail: &Trail, ctx: &Context) -> Result<usize> {
     let graph = Graph::from(trail, false);
     graph
-        .longest_dry_hike((1, 0), (trail.width - 2, trail.height - 1))
+        .longest_dry_hike((1, 0), (trail.width - 2, trail.height - 1), ctx)?
         .ok_or(anyhow!("couldn't find max distance"))
 }
 
@@ -134,29 +142,34 @@ impl Graph {
         self.nodes[self.order.last().unwrap()].distance_from_start
     }
 
-    fn longest_dry_hike(&self, start: Coord, goal: Coord) -> Option<usize> {
+    fn longest_dry_hike(&self, start: Coord, goal: Coord, ctx: &Context) -> Result<Option<usize>> {
         let mut seen = HashSet::new();
-        self.max_distance(start, goal, &mut seen)
+        self.max_distance(start, goal, &mut seen, ctx)
     }
 
-    fn max_distance(&self, start: Coord, goal: Coord, seen: &mut HashSet<Coord>) -> Option<usize> {
+    fn max_distance(
+        &self,
+        start: Coord,
+        goal: Coord,
+        seen: &mut HashSet<Coord>,
+        ctx: &Context,
+    ) -> Result<Option<usize>> {
         if seen.contains(&start) {
-            return None;
+            return Ok(None);
         }
         if start == goal {
-            return Some(0);
+            return Ok(Some(0));
         }
+        ctx.check_cancelled()?;
         seen.insert(start);
-        let max_distance = self.nodes[&start]
-            .dests
-            .iter()
-            .filter_map(|(distance, dest)| {
-                self.max_distance(*dest, goal, seen)
-                    .map(|dist| dist + distance)
-            })
-            .max();
+        let mut max_distance = None;
+        for (distance, dest) in &self.nodes[&start].dests {
+            if let Some(dist) = self.max_distance(*dest, goal, seen, ctx)? {
+                max_distance = max_distance.max(Some(dist + distance));
+            }
+        }
         seen.remove(&start);
-        max_distance
+        Ok(max_distance)
     }
 }
 
@@ -186,7 +199,7 @@ fn get_next_intersection(
     let paths = neighbors
         .iter()
         .filter(|(next_dir, coord)| {
-            !next_dir.is_reverse(dir) && trail.get(*coord).is_some_and(|t| (t != &Tile::Forest))
+            !next_dir.is_reverse(dir) && trail.get(*coord).is_some_and(|t| t != &Tile::Forest)
         })
         .collect_vec();
     if paths.len() == 1 {
@@ -232,7 +245,7 @@ fn get_next_intersection(
             coord: next_coord,
             nb_sources: neighbors
                 .iter()
-                .filter(|(_, coord)| trail.get(*coord).is_some_and(|t| (t != &Tile::Forest)))
+                .filter(|(_, coord)| trail.get(*coord).is_some_and(|t| t != &Tile::Forest))
                 .count(),
             paths: paths.iter().map(|(dir, _)| *dir).collect(),
         }
@@ -1,11 +1,17 @@
 use aoc23::*;
 use itertools::Itertools;
 
-main!(2, 47);
+// part 2 is unsolved, its example (47) is to be checked again once it is
+main!(
+    with_context,
+    (example, part1, 2, area_min = 7, area_max = 27),
+);
 
-#[apply(parse_ordered!)]
+const AREA_MIN: Param<f32> = Param::new("area_min", 200000000000000f32);
+const AREA_MAX: Param<f32> = Param::new("area_max", 400000000000000f32);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, AocParse, AocDisplay)]
 #[delim(", ")]
-#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 struct Coord {
     #[parse(trim)]
     x: f32,
@@ -15,9 +21,8 @@ struct Coord {
     z: f32,
 }
 
-#[apply(parse_ordered!)]
+#[derive(Debug, Default, PartialEq, PartialOrd, AocParse, AocDisplay)]
 #[delim(" @ ")]
-#[derive(Debug, Default, PartialEq, PartialOrd)]
 struct Hailstone {
     #[parse()]
     pos: Coord,
@@ -29,14 +34,8 @@ fn parse(input: &str) -> Result<Vec<Hailstone>> {
     collect_lines(input)
 }
 
-fn part1(hailstones: &[Hailstone]) -> Result<usize> {
-    let test_area = if hailstones.len() < 10 {
-        // example
-        7f32..27f32
-    } else {
-        // real input
-        200000000000000f32..400000000000000f32
-    };
+fn part1(hailstones: &[Hailstone], ctx: &Context) -> Result<usize> {
+    let test_area = ctx.param(&AREA_MIN)?..ctx.param(&AREA_MAX)?;
     let in_test_area = |x, y| test_area.contains(&x) && test_area.contains(&y);
     let lines = hailstones
         .iter()
@@ -54,8 +53,8 @@ fn part1(hailstones: &[Hailstone]) -> Result<usize> {
         .count())
 }
 
-fn part2(_hailstones: &[Hailstone]) -> Result<usize> {
-    Ok(0)
+fn part2(_hailstones: &[Hailstone], _ctx: &Context) -> Result<Answer> {
+    Ok(Answer::Unsolved)
 }
 
 #[derive(Debug, PartialEq, PartialOrd)]
@@ -105,3 +104,14 @@ fn intersection(
 fn in_past(y: f32, h: &Hailstone) -> bool {
     (y - h.pos.y).signum() != h.vel.y.signum()
 }
+
+crate::day_tests! {
+    mod test_display {
+        use super::*;
+
+        #[test]
+        fn test_round_trip() {
+            aoc23::fields::assert_round_trip(parse(INPUT).unwrap());
+        }
+    }
+}
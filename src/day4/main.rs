@@ -1,5 +1,4 @@
 use std::collections::HashSet;
-use std::str::FromStr;
 
 use aoc23::*;
 use itertools::Itertools;
@@ -26,9 +25,8 @@ fn part2(cards: &[Card]) -> Result<usize> {
     Ok(cards_counts.iter().sum())
 }
 
-#[apply(parse_ordered!)]
+#[derive(Debug, Default, Clone, AocParse)]
 #[delim(':')]
-#[derive(Debug, Default, Clone)]
 struct Card {
     #[parse(re("Card +([0-9]+)"))]
     id: usize,
@@ -36,9 +34,8 @@ struct Card {
     numbers: Numbers,
 }
 
-#[apply(parse_ordered!)]
+#[derive(Debug, Default, Clone, AocParse)]
 #[delim('|')]
-#[derive(Debug, Default, Clone)]
 struct Numbers {
     #[parse(collect(' '))]
     winning: HashSet<isize>,
@@ -6,11 +6,6 @@ use itertools::Itertools;
 
 main!(35, 46);
 
-#[cfg(windows)]
-const LINE_FEED: &str = "\r\n";
-#[cfg(not(windows))]
-const LINE_FEED: &str = "\n";
-
 fn parse(input: &str) -> Result<Almanac> {
     input.parse()
 }
@@ -53,11 +48,11 @@ struct Almanac {
     maps: Vec<Mapping>,
 }
 
-#[apply(parse_ordered!)]
-#[delim(LINE_FEED)]
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, AocParse)]
+#[delim('\n')]
 struct Mapping {
     #[parse()]
+    #[allow(dead_code)] // only shown when debugging
     name: String,
     #[parse(collect(remaining))]
     ranges: Vec<MappedRange>,
@@ -133,11 +128,10 @@ impl FromStr for Almanac {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let maps_delim = String::from(LINE_FEED) + LINE_FEED;
-        let (seeds, maps) = s.split_once(&maps_delim).unwrap();
+        let (seeds, maps) = s.split_once("\n\n").unwrap();
         let mut almanac = Almanac {
             seeds: parse_collect(seeds.split_once(':').unwrap().1, ' ')?,
-            maps: parse_collect_str(maps, &maps_delim)?,
+            maps: parse_collect_str(maps, "\n\n")?,
         };
         for m in almanac.maps.iter_mut() {
             m.ranges.sort_by_key(|r| r.source.start)
@@ -1,40 +1,28 @@
-use aoc23::*;
+use aoc23::{
+    parse::{digits, prefixed, separated, spaced, spaces, spaces0, tag, Parser},
+    *,
+};
 use itertools::Itertools;
 
 main!(288, 71503);
 type Input = (Vec<(usize, usize)>, (usize, usize));
 
 fn parse(input: &str) -> Result<Input> {
-    let (time, distance) = input
-        .split_once('\n')
-        .ok_or(parse_error(input, "expected 2 lines"))?;
-    let part1_input = time
-        .split_whitespace()
-        .skip(1)
-        .map(|s| s.parse().unwrap())
-        .zip(
-            distance
-                .split_whitespace()
-                .skip(1)
-                .map(|s| s.parse().unwrap()),
-        )
-        .collect_vec();
-    let part2_input = (
-        time.replace(' ', "")
-            .split_once(':')
-            .unwrap()
-            .1
-            .trim()
-            .parse()?,
-        distance
-            .replace(' ', "")
-            .split_once(':')
-            .unwrap()
-            .1
-            .trim()
-            .parse()?,
-    );
-    Ok((part1_input, part2_input))
+    // the lines can end with spaces, which `separated` leaves after the numbers
+    let numbers = |name| prefixed(name, spaced(separated(digits(), spaces()))).skip(spaces0());
+    let (times, distances): (Vec<_>, Vec<_>) = numbers("Time:")
+        .skip(tag("\n"))
+        .then(numbers("Distance:"))
+        .parse_all(input)?;
+    let number = |digits: &str| digits.parse().map_err(|err| ParseError::wrap(digits, err));
+    let races = times
+        .iter()
+        .zip(&distances)
+        .map(|(time, distance)| -> Result<_> { Ok((number(time)?, number(distance)?)) })
+        .try_collect()?;
+    // the spaces between the digits are to be ignored in part 2
+    let race = (number(&times.concat())?, number(&distances.concat())?);
+    Ok((races, race))
 }
 
 fn part1(races: &Input) -> Result<usize> {
@@ -2,14 +2,13 @@ use aoc23::*;
 use itertools::Itertools;
 
 main!(6440, 5905);
-type Input = Vec<Hand>;
+type Input<'a> = Vec<Hand<'a>>;
 
-#[apply(parse_ordered!)]
+#[derive(Debug, Default, Clone, AocParse)]
 #[delim(' ')]
-#[derive(Debug, Default, Clone)]
-struct Hand {
+struct Hand<'a> {
     #[parse()]
-    hand: String,
+    hand: &'a str,
     #[parse()]
     bid: usize,
 }
@@ -25,8 +24,8 @@ enum Type {
     FiveOfAKind,
 }
 
-fn parse(input: &str) -> Result<Input> {
-    collect_lines(input)
+fn parse(input: &str) -> Result<Input<'_>> {
+    collect_lines_borrowed(input)
 }
 
 fn part(hands: &Input, joker: u8) -> Result<usize> {
@@ -46,7 +45,7 @@ fn part2(hands: &Input) -> Result<usize> {
     part(hands, b'J')
 }
 
-impl Hand {
+impl Hand<'_> {
     fn compare_key(&self, joker: u8) -> (Type, (u8, u8, u8, u8, u8)) {
         (
             self.hand_type(joker),
@@ -1,34 +1,36 @@
 use std::collections::HashMap;
 
 use anyhow::anyhow;
-use aoc23::*;
+use aoc23::{
+    parse::{bracketed, key_value, lines, many, one_of, tag, word, Parser},
+    *,
+};
 
-main!("example1.txt", 6, "example2.txt", 6);
+main!(
+    (example1, part1, 6),
+    (example1, part2, 6),
+    (example2, part2, 6),
+);
 type Network<'a> = HashMap<&'a str, (&'a str, &'a str)>;
 type Input<'a> = (Vec<Dir>, Network<'a>);
 
-const PAREN: &[char] = &['(', ')'];
-
 #[derive(Clone, Copy)]
 enum Dir {
     Left,
     Right,
 }
 
-fn parse(input: &str) -> Result<Input> {
-    let mut lines = input.lines();
-    let directions = lines
-        .next()
-        .ok_or(anyhow!("bad input"))?
-        .chars()
-        .map(|c| if c == 'L' { Dir::Left } else { Dir::Right })
-        .collect();
-    let network = lines
-        .skip(1)
-        .filter_map(|s| s.split_once(" = ("))
-        .filter_map(|(s, lr)| Some((s, lr.trim_matches(PAREN).split_once(", ")?)))
-        .collect();
-    Ok((directions, network))
+fn parse(input: &str) -> Result<Input<'_>> {
+    let directions = many(one_of([("L", Dir::Left), ("R", Dir::Right)]));
+    let node = key_value(
+        word(),
+        " = ",
+        bracketed("(", word().skip(tag(", ")).then(word()), ")"),
+    );
+    directions
+        .skip(tag("\n\n"))
+        .then(lines(node))
+        .parse_all(input)
 }
 
 fn part1((directions, network): &Input) -> Result<usize> {
@@ -56,11 +58,7 @@ fn advance<'a>(current: &'a str, dir: Dir, network: &Network<'a>) -> &'a str {
         Dir::Right => network[current].1,
     }
 }
-fn count_until_exit<'a>(
-    mut current: &'a str,
-    network: &Network<'a>,
-    directions: &Vec<Dir>,
-) -> usize {
+fn count_until_exit<'a>(mut current: &'a str, network: &Network<'a>, directions: &[Dir]) -> usize {
     let mut count = 0;
     let nb_directions = directions.len();
     while !current.ends_with('Z') {
@@ -0,0 +1,187 @@
+use std::{error::Error, fmt, ops::Range};
+
+/// Error of parsing a piece of the input.
+///
+/// When the piece is a slice of the input, [`locate_error`] finds it in the input once the
+/// parsing failed, and the error is then displayed with its line, column and an excerpt.
+#[derive(Debug)]
+pub struct ParseError {
+    text: String,
+    err: String,
+    /// Addresses of the parsed piece, to find it in the input
+    addr: Range<usize>,
+    location: Option<Location>,
+    /// Whether a parser of [`crate::parse`] did not match the text, rather than failing to
+    /// convert it
+    mismatch: bool,
+}
+
+/// Position of a [`ParseError`] in the input, lines and columns starting at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    /// In characters
+    pub column: usize,
+    /// Line of the input where the error starts
+    pub source_line: String,
+    /// Number of characters of the error on this line
+    pub len: usize,
+}
+
+pub fn parse_error(text: &str, err: &str) -> ParseError {
+    ParseError::new(text, err)
+}
+
+impl ParseError {
+    pub fn new(text: &str, err: impl fmt::Display) -> ParseError {
+        let start = text.as_ptr() as usize;
+        ParseError {
+            text: String::from(text),
+            err: err.to_string(),
+            addr: start..start + text.len(),
+            location: None,
+            mismatch: false,
+        }
+    }
+
+    /// Error of a parser of [`crate::parse`] not matching `text`
+    pub(crate) fn mismatch(text: &str, err: impl fmt::Display) -> ParseError {
+        ParseError {
+            mismatch: true,
+            ..ParseError::new(text, err)
+        }
+    }
+
+    /// Whether the error is a parser not matching the start of `s`, having matched none of it
+    pub(crate) fn is_mismatch_at(&self, s: &str) -> bool {
+        self.mismatch && self.addr.start == s.as_ptr() as usize
+    }
+
+    /// Error of parsing `text`, or the error itself if it is a more precise [`ParseError`]
+    /// about a piece of `text`
+    pub fn wrap(text: &str, err: impl Into<anyhow::Error>) -> anyhow::Error {
+        let err = err.into();
+        let outer = ParseError::new(text, format!("{err:#}"));
+        match err.downcast_ref::<ParseError>() {
+            Some(inner) if outer.contains(inner) => err,
+            _ => outer.into(),
+        }
+    }
+
+    /// Like [`ParseError::wrap`], saying what failed to parse, such as the field of a struct
+    pub fn wrap_in(
+        text: &str,
+        what: impl fmt::Display,
+        err: impl Into<anyhow::Error>,
+    ) -> anyhow::Error {
+        let mut err = ParseError::wrap(text, err);
+        if let Some(parse_err) = err.downcast_mut::<ParseError>() {
+            parse_err.err = format!("{what}: {}", parse_err.err);
+        }
+        err
+    }
+
+    pub fn location(&self) -> Option<&Location> {
+        self.location.as_ref()
+    }
+
+    /// Locates the error if the parsed piece is a slice of `input`
+    pub fn locate(&mut self, file: &str, input: &str) {
+        let Some(start) = self.addr.start.checked_sub(input.as_ptr() as usize) else {
+            return;
+        };
+        if start + self.addr.len() <= input.len() {
+            self.location = Some(Location::new(file, input, start..start + self.addr.len()));
+        }
+    }
+
+    fn contains(&self, other: &ParseError) -> bool {
+        self.addr.start <= other.addr.start && other.addr.end <= self.addr.end
+    }
+}
+
+impl Location {
+    fn new(file: &str, input: &str, span: Range<usize>) -> Location {
+        let line_start = input[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[span.start..]
+            .find('\n')
+            .map_or(input.len(), |i| span.start + i);
+        Location {
+            file: String::from(file),
+            line: input[..span.start].matches('\n').count() + 1,
+            column: input[line_start..span.start].chars().count() + 1,
+            source_line: String::from(input[line_start..line_end].trim_end_matches('\r')),
+            len: input[span.start..span.end.min(line_end)].chars().count(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(loc) = &self.location else {
+            return write!(f, "Failed to parse '{}': {}", self.text, self.err);
+        };
+        let err = if self.err.is_empty() {
+            "failed to parse"
+        } else {
+            &self.err
+        };
+        let number = loc.line.to_string();
+        let margin = " ".repeat(number.len());
+        writeln!(f, "{}:{}:{}: {err}", loc.file, loc.line, loc.column)?;
+        writeln!(f, "{margin} |")?;
+        writeln!(f, "{number} | {}", loc.source_line)?;
+        write!(
+            f,
+            "{margin} | {}{}",
+            " ".repeat(loc.column - 1),
+            "^".repeat(loc.len.max(1))
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Locates the [`ParseError`] of a failed parsing of `input`, read from `file`
+pub fn locate_error(mut err: anyhow::Error, file: &str, input: &str) -> anyhow::Error {
+    if let Some(parse_err) = err.downcast_mut::<ParseError>() {
+        parse_err.locate(file, input);
+    }
+    err
+}
+
+#[cfg(test)]
+mod test_error {
+    use std::str::FromStr;
+
+    use crate::{collect_lines, error::locate_error, parse_collect, Result};
+
+    #[test]
+    fn test_locate_error() {
+        let rows = collect_lines::<Row, Vec<_>>("1,2\n3").unwrap();
+        assert_eq!(rows[1].0, [3]);
+
+        let input = "1,2,3\n4,5,6\n7,x8,9\n";
+        let lines = collect_lines::<Row, Vec<_>>(input);
+        let err = locate_error(lines.unwrap_err(), "input.txt", input);
+        assert_eq!(
+            err.to_string(),
+            "input.txt:3:3: invalid digit found in string\n  \
+               |\n\
+             3 | 7,x8,9\n  \
+               |   ^^"
+        );
+    }
+
+    #[derive(Debug)]
+    struct Row(Vec<u32>);
+
+    impl FromStr for Row {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self> {
+            Ok(Row(parse_collect(s, ',')?))
+        }
+    }
+}
@@ -0,0 +1,465 @@
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Display, Formatter},
+    str::FromStr,
+};
+
+use itertools::Itertools;
+pub use regex::Regex;
+
+use crate::{ParseError, Result};
+
+/// Parsing of the types that can borrow from the string they are parsed from.
+///
+/// `derive(AocParse)` implements it in place of `FromStr` for the types with a lifetime.
+pub trait ParseBorrowed<'a>: Sized {
+    fn parse(s: &'a str) -> Result<Self>;
+}
+
+impl<'a> ParseBorrowed<'a> for &'a str {
+    fn parse(s: &'a str) -> Result<Self> {
+        Ok(s)
+    }
+}
+
+impl<'a> ParseBorrowed<'a> for Cow<'a, str> {
+    fn parse(s: &'a str) -> Result<Self> {
+        Ok(Cow::Borrowed(s))
+    }
+}
+
+// Helpers of the code generated by `derive(AocParse)`
+
+/// Next piece of `string`, holding `what`
+pub fn next<'a>(
+    pieces: &mut impl Iterator<Item = &'a str>,
+    string: &'a str,
+    what: &str,
+    delim: impl Debug,
+) -> Result<&'a str> {
+    pieces.next().ok_or_else(|| {
+        ParseError::new(
+            string,
+            format!("missing {what}, expected one more {delim:?}"),
+        )
+        .into()
+    })
+}
+
+/// Parses `s`, the text of `what` (a field, group or variant)
+pub fn parse<T>(s: &str, what: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    s.parse().map_err(|err| ParseError::wrap_in(s, what, err))
+}
+
+pub fn collect<'a, T, C>(pieces: impl Iterator<Item = &'a str>, what: &str) -> Result<C>
+where
+    T: FromStr,
+    T::Err: Into<anyhow::Error>,
+    C: FromIterator<T>,
+{
+    pieces.map(|s| parse(s, what)).collect()
+}
+
+/// Parses `s`, the text of `what`, keeping slices of it
+pub fn parse_borrowed<'a, T: ParseBorrowed<'a>>(s: &'a str, what: &str) -> Result<T> {
+    T::parse(s).map_err(|err| ParseError::wrap_in(s, what, err))
+}
+
+pub fn collect_borrowed<'a, T, C>(pieces: impl Iterator<Item = &'a str>, what: &str) -> Result<C>
+where
+    T: ParseBorrowed<'a>,
+    C: FromIterator<T>,
+{
+    pieces.map(|s| parse_borrowed(s, what)).collect()
+}
+
+/// First group of `re` in `s`
+pub fn capture<'a>(re: &Regex, s: &'a str, what: &str) -> Result<&'a str> {
+    match re.captures(s).and_then(|cap| cap.get(1)) {
+        Some(group) => Ok(group.as_str()),
+        None => Err(ParseError::new(
+            s,
+            format!("{what}: failed to match regex {}", re.as_str()),
+        ))?,
+    }
+}
+
+/// Error of a piece matching none of the fields or variants
+pub fn unmatched(piece: &str, what: &str, names: &[&str]) -> anyhow::Error {
+    let names = names.iter().map(|name| format!("`{name}`")).join(", ");
+    ParseError::new(piece, format!("matches none of the {what} {names}")).into()
+}
+
+/// Error of `s` not matching the regex of a struct, naming the first group that fails to match.
+///
+/// `prefixes` holds the top-level groups of `re` with the regex up to their end: the group of
+/// the first prefix that does not match is the culprit.
+pub fn unmatched_regex(s: &str, re: &Regex, prefixes: &[(&str, &str)]) -> anyhow::Error {
+    let group = prefixes
+        .iter()
+        .find(|(_, prefix)| Regex::new(prefix).is_ok_and(|prefix| !prefix.is_match(s)));
+    let err = match group {
+        Some((group, _)) => format!("group `{group}` failed to match regex {}", re.as_str()),
+        None => format!("failed to match regex {}", re.as_str()),
+    };
+    ParseError::new(s, err).into()
+}
+
+/// Error of an optional group of the regex of a struct matching nothing, for a field that
+/// cannot be left out
+pub fn missing_group(s: &str, group: &str) -> anyhow::Error {
+    ParseError::new(s, format!("missing group `{group}`, it matched nothing")).into()
+}
+
+/// Writes `delim` before every piece but the first
+pub fn separate(f: &mut Formatter, first: &mut bool, delim: impl Display) -> fmt::Result {
+    if !std::mem::take(first) {
+        write!(f, "{delim}")?;
+    }
+    Ok(())
+}
+
+/// Writes the items between the delimiters
+pub fn write_joined<T: Display>(
+    f: &mut Formatter,
+    items: impl IntoIterator<Item = T>,
+    delim: impl Display,
+) -> fmt::Result {
+    let mut first = true;
+    for item in items {
+        separate(f, &mut first, &delim)?;
+        write!(f, "{item}")?;
+    }
+    Ok(())
+}
+
+/// Asserts that the values parse back from their text, for the tests of the types deriving
+/// `AocDisplay`
+#[track_caller]
+pub fn assert_round_trip<T>(values: impl IntoIterator<Item = T>)
+where
+    T: FromStr<Err: Debug> + Display + PartialEq + Debug,
+{
+    for value in values {
+        assert_eq!(T::from_str(&value.to_string()).unwrap(), value);
+    }
+}
+
+#[cfg(test)]
+mod test_fields {
+    use crate::{AocDisplay, AocParse, FromStr, ParseBorrowed};
+
+    #[derive(Debug, PartialEq, AocParse, AocDisplay)]
+    #[delim(':', write = ": ")]
+    struct Game {
+        #[parse(re("Game ([0-9]+)"))]
+        id: usize,
+        #[parse(collect(';', write = "; "))]
+        sets: Vec<Cubes>,
+    }
+
+    #[derive(Debug, PartialEq, AocParse, AocDisplay)]
+    #[delim(',', write = ", ")]
+    struct Cubes {
+        #[parse("([0-9]+) red")]
+        red: usize,
+        #[parse("([0-9]+) blue")]
+        blue: usize,
+    }
+
+    #[derive(Debug, PartialEq, AocParse, AocDisplay)]
+    #[delim(" @ ")]
+    struct Segment {
+        #[parse(trim)]
+        start: i32,
+        #[parse(collect(", "))]
+        end: Vec<i32>,
+    }
+
+    #[derive(Debug, PartialEq, AocParse)]
+    #[delim(':')]
+    struct Rule {
+        #[parse()]
+        cond: Option<String>,
+        #[parse(default = 1)]
+        weight: u32,
+        #[parse()]
+        dest: String,
+    }
+
+    /// Like [`Rule`], with the `Option` last so that it can be written back
+    #[derive(Debug, PartialEq, AocParse, AocDisplay)]
+    #[delim(' ')]
+    struct Step {
+        #[parse()]
+        name: String,
+        #[parse(default = 1)]
+        count: u32,
+        #[parse()]
+        note: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, AocParse, AocDisplay)]
+    #[parse(re(r"^(?<name>[a-z]+)=(?<x>-?[0-9]+),(?<y>[0-9]+)(?: w(?<weight>[0-9]+))?$"))]
+    struct Point {
+        name: String,
+        x: i32,
+        y: i32,
+        weight: Option<u32>,
+    }
+
+    #[derive(Debug, PartialEq, AocParse, AocDisplay)]
+    #[delim(" -> ")]
+    struct Connection<'a> {
+        #[parse()]
+        source: Node<'a>,
+        #[parse(collect(", "))]
+        dests: Vec<&'a str>,
+        #[parse(default)]
+        weight: u32,
+    }
+
+    #[derive(Debug, PartialEq, AocParse, AocDisplay)]
+    enum Node<'a> {
+        #[parse(prefix = "%")]
+        Named(&'a str),
+        #[parse(re("^#(.*)$"))]
+        Numbered(u32),
+    }
+
+    #[derive(Debug, PartialEq, AocParse, AocDisplay)]
+    enum Module {
+        #[parse(prefix = "%")]
+        FlipFlop(String),
+        #[parse(prefix = "&")]
+        Conjunction,
+        #[parse(re("^broadcaster$"))]
+        Broadcast,
+        #[parse(re("^#(.*)$"))]
+        Numbered(u32),
+    }
+
+    #[test]
+    fn test_derive_aoc_parse() {
+        assert_eq!(
+            Game::from_str("Game 3: 1 red, 2 blue; 4 blue").unwrap(),
+            Game {
+                id: 3,
+                sets: vec![Cubes { red: 1, blue: 2 }, Cubes { red: 0, blue: 4 }],
+            }
+        );
+        assert_eq!(
+            Segment::from_str(" -1  @ 2, 3").unwrap(),
+            Segment {
+                start: -1,
+                end: vec![2, 3],
+            }
+        );
+
+        let err = |s| format!("{:#}", Game::from_str(s).unwrap_err());
+        assert_eq!(
+            err("Game 3"),
+            "Failed to parse 'Game 3': missing field `sets`, expected one more ':'"
+        );
+        assert_eq!(
+            err("Game x: 1 red"),
+            "Failed to parse 'Game x': field `id`: failed to match regex Game ([0-9]+)"
+        );
+        assert_eq!(
+            err("Game 3: 1 red, 2 green"),
+            "Failed to parse ' 2 green': field `sets`: matches none of the fields `red`, `blue`"
+        );
+        assert_eq!(
+            format!("{:#}", Segment::from_str("x @ 1").unwrap_err()),
+            "Failed to parse 'x': field `start`: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_optional_fields() {
+        let rule = |cond: Option<&str>, weight, dest: &str| Rule {
+            cond: cond.map(String::from),
+            weight,
+            dest: String::from(dest),
+        };
+        assert_eq!(Rule::from_str("A").unwrap(), rule(None, 1, "A"));
+        assert_eq!(Rule::from_str("x<3:A").unwrap(), rule(Some("x<3"), 1, "A"));
+        assert_eq!(
+            Rule::from_str("x<3:5:A").unwrap(),
+            rule(Some("x<3"), 5, "A")
+        );
+        assert!(Rule::from_str("").is_ok());
+    }
+
+    #[test]
+    fn test_struct_regex() {
+        let point = |x, y, weight| Point {
+            name: String::from("p"),
+            x,
+            y,
+            weight,
+        };
+        assert_eq!(Point::from_str("p=-1,2").unwrap(), point(-1, 2, None));
+        assert_eq!(Point::from_str("p=1,2 w3").unwrap(), point(1, 2, Some(3)));
+
+        let err = |s| format!("{:#}", Point::from_str(s).unwrap_err());
+        assert_eq!(
+            err("p=1;2"),
+            "Failed to parse 'p=1;2': group `y` failed to match regex \
+             ^(?<name>[a-z]+)=(?<x>-?[0-9]+),(?<y>[0-9]+)(?: w(?<weight>[0-9]+))?$"
+        );
+        assert_eq!(
+            err("p=99999999999,2"),
+            "Failed to parse '99999999999': group `x`: number too large to fit in target type"
+        );
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let input = String::from("%ab -> c, d");
+        let connection = Connection::parse(&input).unwrap();
+        assert_eq!(connection.source, Node::Named("ab"));
+        assert_eq!(connection.dests, ["c", "d"]);
+        assert!(std::ptr::eq(connection.dests[0], &input[7..8]));
+        assert_eq!(
+            Connection::parse("#4 -> a -> 2").unwrap(),
+            Connection {
+                source: Node::Numbered(4),
+                dests: vec!["a"],
+                weight: 2,
+            }
+        );
+        assert_eq!(
+            format!("{:#}", Connection::parse("#x -> a").unwrap_err()),
+            "Failed to parse 'x': field `source`: variant `Numbered`: invalid digit found in string"
+        );
+    }
+
+    /// Pseudo-random generator of the values of the round-trip tests
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: u64) -> u64 {
+            // xorshift64
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+
+        fn word(&mut self) -> String {
+            (0..1 + self.below(4))
+                .map(|_| char::from(b'a' + self.below(26) as u8))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let point = Point {
+            name: String::from("p"),
+            x: -1,
+            y: 2,
+            weight: Some(3),
+        };
+        assert_eq!(point.to_string(), "p=-1,2 w3");
+        assert_eq!(
+            Segment {
+                start: 1,
+                end: vec![2, 3],
+            }
+            .to_string(),
+            "1 @ 2, 3"
+        );
+        assert_eq!(Module::Broadcast.to_string(), "broadcaster");
+
+        let line = "Game 3: 1 red, 2 blue; 4 blue";
+        assert_eq!(Game::from_str(line).unwrap().to_string(), line);
+        assert_eq!(Cubes { red: 0, blue: 0 }.to_string(), "0 red");
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..200 {
+            let game = Game {
+                id: rng.below(1000) as usize,
+                sets: (0..rng.below(4))
+                    .map(|_| Cubes {
+                        red: rng.below(20) as usize,
+                        blue: rng.below(20) as usize,
+                    })
+                    .collect(),
+            };
+            assert_eq!(Game::from_str(&game.to_string()).unwrap(), game);
+
+            let segment = Segment {
+                start: rng.below(100) as i32 - 50,
+                end: (0..1 + rng.below(3)).map(|n| n as i32).collect(),
+            };
+            assert_eq!(Segment::from_str(&segment.to_string()).unwrap(), segment);
+
+            let step = Step {
+                name: rng.word(),
+                count: rng.below(10) as u32,
+                note: (rng.below(2) == 0).then(|| rng.word()),
+            };
+            assert_eq!(Step::from_str(&step.to_string()).unwrap(), step);
+
+            let point = Point {
+                name: rng.word(),
+                x: rng.below(100) as i32 - 50,
+                y: rng.below(100) as i32,
+                weight: (rng.below(2) == 0).then(|| rng.below(9) as u32),
+            };
+            assert_eq!(Point::from_str(&point.to_string()).unwrap(), point);
+
+            let module = match rng.below(4) {
+                0 => Module::FlipFlop(rng.word()),
+                1 => Module::Broadcast,
+                2 => Module::Conjunction,
+                _ => Module::Numbered(rng.below(100) as u32),
+            };
+            assert_eq!(Module::from_str(&module.to_string()).unwrap(), module);
+
+            let names = [rng.word(), rng.word(), rng.word()];
+            let connection = Connection {
+                source: match rng.below(2) {
+                    0 => Node::Named(&names[0]),
+                    _ => Node::Numbered(rng.below(100) as u32),
+                },
+                dests: names[1..=rng.below(2) as usize + 1]
+                    .iter()
+                    .map(String::as_str)
+                    .collect(),
+                weight: rng.below(9) as u32,
+            };
+            let text = connection.to_string();
+            assert_eq!(Connection::parse(&text).unwrap(), connection);
+        }
+    }
+
+    #[test]
+    fn test_enum() {
+        assert_eq!(
+            Module::from_str("%ab").unwrap(),
+            Module::FlipFlop(String::from("ab"))
+        );
+        assert_eq!(Module::from_str("broadcaster").unwrap(), Module::Broadcast);
+        assert_eq!(Module::from_str("&").unwrap(), Module::Conjunction);
+        assert_eq!(Module::from_str("#12").unwrap(), Module::Numbered(12));
+        assert_eq!(
+            format!("{:#}", Module::from_str("#x").unwrap_err()),
+            "Failed to parse 'x': variant `Numbered`: invalid digit found in string"
+        );
+        assert_eq!(
+            format!("{:#}", Module::from_str("&ab").unwrap_err()),
+            "Failed to parse '&ab': matches none of the variants `FlipFlop`, `Conjunction`, \
+             `Broadcast`, `Numbered`"
+        );
+    }
+}
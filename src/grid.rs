@@ -3,6 +3,9 @@ use std::{
     fmt::Display,
     ops::{Index, IndexMut, Range},
 };
+use thiserror::Error;
+
+use crate::{ParseError, Result};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Grid<Item> {
@@ -35,6 +38,35 @@ impl Dir {
 
 pub type Neighbor<Item> = (Dir, Option<Item>);
 
+/// Error of [`Grid::try_from_lines`], located in the input by the [`ParseError`] holding it
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    #[error("unexpected '{}' at {coord:?}: {err}", .byte.escape_ascii())]
+    Tile { coord: Coord, byte: u8, err: String },
+    #[error("row {y} has {len} tiles instead of {width}")]
+    Width { y: usize, len: usize, width: usize },
+}
+
+/// The character of `line` that contains its byte `x`
+fn char_at(line: &str, x: usize) -> &str {
+    let start = (0..=x).rfind(|&i| line.is_char_boundary(i)).unwrap_or(0);
+    let end = (x + 1..=line.len())
+        .find(|&i| line.is_char_boundary(i))
+        .unwrap_or(line.len());
+    &line[start..end]
+}
+
+impl<Item> Grid<Item>
+where
+    Item: TryFrom<u8>,
+    Item::Error: Display,
+{
+    /// Fallible [`Grid::from_lines`], failing on the first byte that is not a tile
+    pub fn try_from_lines(input: &str) -> Result<Self> {
+        Self::try_from_lines_mapped(input, Item::try_from)
+    }
+}
+
 impl<Item> Grid<Item>
 where
     Item: From<u8>,
@@ -107,6 +139,55 @@ impl<Item> Grid<Item> {
         }
     }
 
+    /// Fallible [`Grid::from_lines_mapped`], skipping empty lines and checking that all the lines
+    /// have the same width.
+    ///
+    /// The errors are [`ParseError`]s about the tile or the line that failed, whose coordinates
+    /// are the ones in the input, empty lines included.
+    pub fn try_from_lines_mapped<E: Display>(
+        input: &str,
+        f: impl Fn(u8) -> Result<Item, E>,
+    ) -> Result<Grid<Item>> {
+        let lines = input
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.is_empty())
+            .map(|(y, l)| {
+                let tiles = l
+                    .bytes()
+                    .enumerate()
+                    .map(|(x, byte)| {
+                        f(byte).map_err(|err| {
+                            let tile = GridError::Tile {
+                                coord: (x, y),
+                                byte,
+                                err: err.to_string(),
+                            };
+                            ParseError::new(char_at(l, x), tile).into()
+                        })
+                    })
+                    .try_collect::<_, _, anyhow::Error>()?;
+                Ok((y, l, tiles))
+            })
+            .collect::<Result<Vec<(usize, &str, Vec<_>)>>>()?;
+        let width = lines.first().map_or(0, |(_, _, tiles)| tiles.len());
+        if let Some((y, line, tiles)) = lines.iter().find(|(_, _, tiles)| tiles.len() != width) {
+            let err = GridError::Width {
+                y: *y,
+                len: tiles.len(),
+                width,
+            };
+            return Err(ParseError::new(line, err).into());
+        }
+        let lines = lines.into_iter().map(|(_, _, tiles)| tiles).collect_vec();
+        let height = lines.len();
+        Ok(Grid {
+            lines,
+            width,
+            height,
+        })
+    }
+
     pub fn mapped<MappedItem>(&self, f: impl Fn(&Item) -> MappedItem) -> Grid<MappedItem> {
         Grid::<MappedItem> {
             lines: self
@@ -336,3 +417,41 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test_grid {
+    use crate::{grid::Grid, locate_error};
+
+    fn digit(byte: u8) -> Result<u32, &'static str> {
+        (byte as char).to_digit(10).ok_or("not a digit")
+    }
+
+    #[test]
+    fn test_try_from_lines() {
+        let grid = Grid::try_from_lines_mapped("12\n34\n", digit).unwrap();
+        assert_eq!(grid.lines, [[1, 2], [3, 4]]);
+        let err = |input| {
+            let err = Grid::try_from_lines_mapped(input, digit).unwrap_err();
+            locate_error(err, "input.txt", input).to_string()
+        };
+        assert_eq!(
+            err("12\n\n3x\n"),
+            "input.txt:3:2: unexpected 'x' at (1, 2): not a digit\n  \
+               |\n\
+             3 | 3x\n  \
+               |  ^"
+        );
+        assert_eq!(
+            err("12\n3\n"),
+            "input.txt:2:1: row 1 has 1 tiles instead of 2\n  \
+               |\n\
+             2 | 3\n  \
+               | ^"
+        );
+        let err = Grid::<u8>::try_from_lines_mapped("\t", |_| Err("tab")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse '\t': unexpected '\\t' at (0, 0): tab"
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context as _};
+
+use crate::{bench::Stats, Answer, DayReport, Result};
+
+/// History file used when `--history` is not given, relative to the working directory
+pub const DEFAULT_HISTORY: &str = "aoc-history.csv";
+
+pub fn history_path(path: Option<&Path>) -> PathBuf {
+    path.map_or_else(|| PathBuf::from(DEFAULT_HISTORY), Path::to_path_buf)
+}
+
+/// Timing of one step (parse, part1 or part2) of one day in a labelled run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub label: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub day: u32,
+    pub step: String,
+    pub time: Duration,
+}
+
+impl Entry {
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{}",
+            self.label,
+            self.timestamp,
+            self.day,
+            self.step,
+            self.time.as_nanos()
+        )
+    }
+
+    fn from_csv(line: &str) -> Option<Entry> {
+        let mut fields = line.split(',');
+        let entry = Entry {
+            label: String::from(fields.next()?),
+            timestamp: fields.next()?.parse().ok()?,
+            day: fields.next()?.parse().ok()?,
+            step: String::from(fields.next()?),
+            time: Duration::from_nanos(fields.next()?.parse().ok()?),
+        };
+        fields.next().is_none().then_some(entry)
+    }
+}
+
+/// Checks that a label can be stored in the history file as is
+pub fn parse_label(s: &str) -> Result<String> {
+    if s.is_empty() || s.contains([',', '"', '\n', '\r']) {
+        bail!("a label must be non-empty and cannot contain commas, quotes or newlines");
+    }
+    Ok(String::from(s))
+}
+
+/// Timings of the steps of a day that succeeded and are solved; the median is used for
+/// benchmarked steps
+pub fn entries(label: &str, day: u32, report: Option<&DayReport>) -> Vec<Entry> {
+    let Some(report) = report else {
+        return Vec::new();
+    };
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let bench = report.bench.as_ref();
+    let median = |stats: Option<Stats>| stats.map(|s| s.median);
+    let parts = [
+        ("part1", &report.part1, median(bench.and_then(|b| b.part1))),
+        ("part2", &report.part2, median(bench.and_then(|b| b.part2))),
+    ];
+    let parse = (
+        "parse",
+        Some(median(bench.and_then(|b| b.parse)).unwrap_or(report.parse_time)),
+    );
+    let parts = parts.into_iter().map(|(step, part, benched)| {
+        let time = part
+            .as_ref()
+            .filter(|p| p.answer.as_ref().is_ok_and(Answer::is_solved))
+            .map(|p| benched.unwrap_or(p.time));
+        (step, time)
+    });
+    [parse]
+        .into_iter()
+        .chain(parts)
+        .filter_map(|(step, time)| {
+            Some(Entry {
+                label: String::from(label),
+                timestamp,
+                day,
+                step: String::from(step),
+                time: time?,
+            })
+        })
+        .collect()
+}
+
+pub fn append(path: &Path, entries: &[Entry]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open history file '{}'", path.display()))?;
+    let mut lines = String::new();
+    for entry in entries {
+        lines.push_str(&entry.to_csv());
+        lines.push('\n');
+    }
+    file.write_all(lines.as_bytes())
+        .with_context(|| format!("failed to write history file '{}'", path.display()))
+}
+
+pub fn load(path: &Path) -> Result<Vec<Entry>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            bail!(
+                "history file '{}' does not exist, record runs with --label first",
+                path.display()
+            )
+        }
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("failed to read history file '{}'", path.display()))
+        }
+    };
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            Entry::from_csv(line)
+                .with_context(|| format!("{}:{}: invalid entry", path.display(), i + 1))
+        })
+        .collect()
+}
+
+/// Change of the time of one step between two labels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delta {
+    pub day: u32,
+    pub step: String,
+    pub before: Duration,
+    pub after: Duration,
+}
+
+impl Delta {
+    /// Relative change in percent, positive when slower, or `None` when the time before is zero
+    pub fn change(&self) -> Option<f64> {
+        (!self.before.is_zero())
+            .then(|| (self.after.as_secs_f64() / self.before.as_secs_f64() - 1.0) * 100.0)
+    }
+}
+
+/// Compares the steps recorded under both labels.
+///
+/// A label recorded several times keeps the fastest time of each step, the least noisy one.
+pub fn diff(entries: &[Entry], before: &str, after: &str) -> Vec<Delta> {
+    let fastest = |label: &str| {
+        let mut times = BTreeMap::new();
+        for e in entries.iter().filter(|e| e.label == label) {
+            times
+                .entry((e.day, step_order(&e.step), e.step.as_str()))
+                .and_modify(|t: &mut Duration| *t = (*t).min(e.time))
+                .or_insert(e.time);
+        }
+        times
+    };
+    let after = fastest(after);
+    fastest(before)
+        .into_iter()
+        .filter_map(|(key @ (day, _, step), before)| {
+            Some(Delta {
+                day,
+                step: String::from(step),
+                before,
+                after: *after.get(&key)?,
+            })
+        })
+        .collect()
+}
+
+fn step_order(step: &str) -> u8 {
+    match step {
+        "parse" => 0,
+        "part1" => 1,
+        "part2" => 2,
+        _ => 3,
+    }
+}
+
+/// Prints the deltas, and returns the number of regressions above `threshold` percent
+pub fn print_diff(deltas: &[Delta], before: &str, after: &str, threshold: f64) -> usize {
+    println!("\nTimings from '{before}' to '{after}':");
+    let rows = deltas
+        .iter()
+        .map(|d| {
+            let regression = d.change().is_some_and(|change| change > threshold);
+            [
+                d.day.to_string(),
+                d.step.clone(),
+                format!("{:.2?}", d.before),
+                format!("{:.2?}", d.after),
+                d.change()
+                    .map_or_else(|| String::from("n/a"), |change| format!("{change:+.1}%")),
+                String::from(if regression { "REGRESSION" } else { "" }),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let header = ["day", "step", before, after, "change", ""].map(String::from);
+    let widths = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .chain([&header])
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+    for row in [&header].into_iter().chain(&rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, width))| {
+                if i == 0 {
+                    format!("{cell:>width$}")
+                } else {
+                    format!("{cell:<width$}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("    {}", line.trim_end());
+    }
+    rows.iter().filter(|row| !row[5].is_empty()).count()
+}
+
+#[cfg(test)]
+mod test_history {
+    use std::time::Duration;
+
+    use crate::history::{diff, Delta, Entry};
+
+    fn entry(label: &str, day: u32, step: &str, micros: u64) -> Entry {
+        Entry {
+            label: String::from(label),
+            timestamp: 1700000000,
+            day,
+            step: String::from(step),
+            time: Duration::from_micros(micros),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let entries = [
+            entry("old", 17, "part2", 900),
+            entry("old", 17, "part2", 800),
+            entry("old", 17, "part1", 100),
+            entry("old", 3, "parse", 10),
+            entry("new", 17, "part2", 1000),
+            entry("new", 17, "part1", 90),
+        ];
+        assert_eq!(Entry::from_csv(&entries[0].to_csv()).unwrap(), entries[0]);
+        let deltas = diff(&entries, "old", "new");
+        assert_eq!(
+            deltas,
+            [
+                Delta {
+                    day: 17,
+                    step: String::from("part1"),
+                    before: Duration::from_micros(100),
+                    after: Duration::from_micros(90),
+                },
+                Delta {
+                    day: 17,
+                    step: String::from("part2"),
+                    before: Duration::from_micros(800),
+                    after: Duration::from_micros(1000),
+                },
+            ]
+        );
+        assert_eq!(deltas[1].change().map(f64::round), Some(25.0));
+        let zero = Delta {
+            before: Duration::ZERO,
+            ..deltas[0].clone()
+        };
+        assert_eq!(zero.change(), None);
+    }
+}
@@ -1,86 +1,227 @@
-use std::{fmt::Display, time::Duration};
+use std::{
+    borrow::Cow,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 pub use anyhow::Result;
+pub use aoc23_derive::{AocDisplay, AocParse};
 use clap::{Parser, ValueEnum};
-pub use macro_rules_attribute::apply;
+pub use pastey;
 pub use std::str::FromStr;
 use thiserror::Error;
 
+// the code generated by `derive(AocParse)` refers to `aoc23`, also in the tests of this crate
+extern crate self as aoc23;
+
+pub mod alloc;
+pub mod answer;
+pub mod answers;
+pub mod bench;
+pub mod capture;
+pub mod context;
 pub mod crt;
+pub mod error;
+pub mod fields;
 pub mod grid;
+pub mod history;
+pub mod output;
+pub mod parse;
+pub mod raster;
+pub mod runner;
+pub mod solution;
+pub mod visualize;
+
+pub use answer::Answer;
+pub use context::{Cancelled, Context, Param};
+pub use error::{locate_error, parse_error, ParseError};
+pub use fields::ParseBorrowed;
+pub use output::print_report;
+pub use runner::{
+    day_number, print_errors, print_results, print_totals, run_solution, run_solution_with_timeout,
+    solve_example, Day, DayReport, PartReport, Timeout,
+};
+pub use solution::{FnSolution, Solution};
+pub use visualize::{TerminalVisualizer, Visualizer};
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
 pub enum Part {
     Part1,
     Part2,
 }
 
-#[derive(Parser)]
+#[derive(Parser, Clone, Default)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
     #[arg(value_enum)]
     pub part: Option<Part>,
+    /// Read the puzzle input from this file instead of the embedded input.txt ("-" for stdin)
+    #[arg(short, long, value_name = "PATH")]
+    pub input: Option<PathBuf>,
+    /// Benchmark the parse and the parts over N iterations
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "100")]
+    pub bench: Option<usize>,
+    /// Only parse the input once when benchmarking, and time the parts alone
+    #[arg(long, requires = "bench")]
+    pub reuse_input: bool,
+    /// Output format of the results and timings
+    #[arg(long, value_enum, default_value_t)]
+    pub format: output::Format,
+    /// Compare the answers with the expected ones from answers.toml
+    #[arg(long)]
+    pub check: bool,
+    /// Set a puzzle parameter, overriding the value used for the real input
+    #[arg(long = "param", value_name = "KEY=VALUE", value_parser = context::parse_key_value)]
+    pub params: Vec<(String, String)>,
+    /// Append the timings to the history file under this label
+    #[arg(long, value_name = "LABEL", value_parser = history::parse_label)]
+    pub label: Option<String>,
+    /// History file of the labelled timings [default: aoc-history.csv]
+    #[arg(long, value_name = "PATH")]
+    pub history: Option<PathBuf>,
+    /// Show the frames emitted by the day in the terminal
+    #[arg(long, conflicts_with = "bench")]
+    pub visualize: bool,
+    /// Record the frames drawn as images into this GIF, and the last one into a PNG next to it
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["bench", "visualize"])]
+    pub record: Option<PathBuf>,
+    /// Delay after each frame when visualizing or recording
+    #[arg(long, value_name = "MS", default_value_t = 10)]
+    pub frame_delay: u64,
+    /// Give up on a part after this duration, such as 500ms, 30s or 2m (seconds by default)
+    #[arg(long, value_name = "DUR", value_parser = runner::parse_timeout)]
+    pub timeout: Option<Duration>,
 }
 
 pub fn get_cli_args() -> Args {
     Args::parse()
 }
 
-pub fn print_results(
-    t_parse: Duration,
-    p1: Option<Result<impl Display>>,
-    t1: Option<Duration>,
-    p2: Option<Result<impl Display>>,
-    t2: Option<Duration>,
-) -> Result<()> {
-    println!("\nResults:");
-    if let Some(r) = p1 {
-        println!("    part1: {}", r?);
+#[derive(Error, Debug)]
+pub enum InputError {
+    #[error("input file '{0}' does not exist")]
+    NotFound(PathBuf),
+    #[error("failed to read input file '{path}': {source}")]
+    Unreadable { path: PathBuf, source: io::Error },
+    #[error("failed to read input from stdin: {0}")]
+    Stdin(io::Error),
+}
+
+/// Returns the input selected on the command line, or `embedded` if none was given.
+pub fn read_input(
+    path: Option<&Path>,
+    embedded: &'static str,
+) -> Result<Cow<'static, str>, InputError> {
+    match path {
+        None => Ok(Cow::Borrowed(embedded)),
+        Some(path) if path == Path::new("-") => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(InputError::Stdin)?;
+            Ok(Cow::Owned(input))
+        }
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(input) => Ok(Cow::Owned(input)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Err(InputError::NotFound(path.to_path_buf()))
+            }
+            Err(source) => Err(InputError::Unreadable {
+                path: path.to_path_buf(),
+                source,
+            }),
+        },
     }
-    if let Some(r) = p2 {
-        println!("    part2: {}", r?);
+}
+
+/// How `main!` prepares the input of a day before parsing it, see [`normalize_input`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InputMode {
+    Normalized,
+    /// For the days that must see the input as it is, declared with `main!(raw_input, ...)`
+    Raw,
+}
+
+impl InputMode {
+    pub fn prepare(self, input: &str) -> Cow<'_, str> {
+        match self {
+            InputMode::Normalized => normalize_input(input),
+            InputMode::Raw => Cow::Borrowed(input),
+        }
     }
-    println!("\nTimings:");
-    println!("    parse: {:?}", t_parse);
-    if let Some(t) = t1 {
-        println!("    part1: {:?}", t);
+}
+
+/// Strips the byte order mark and the trailing line endings, and converts CRLF line endings to LF.
+///
+/// The other trailing whitespace is kept, as it can be part of the last line. The input is only
+/// copied if it has CRLF line endings.
+pub fn normalize_input(input: &str) -> Cow<'_, str> {
+    let mut input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    while let Some(line) = input.strip_suffix('\n') {
+        input = line.strip_suffix('\r').unwrap_or(line);
     }
-    if let Some(t) = t2 {
-        println!("    part2: {:?}", t);
+    if input.contains("\r\n") {
+        Cow::Owned(input.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(input)
     }
-    Ok(())
 }
 
+/// Generates the `main` and `run` functions of a day, and the tests of its examples.
+///
+/// The input is normalized with [`normalize_input`] first, unless the arguments start with
+/// `raw_input`.
 #[macro_export]
 macro_rules! main {
     () => {
+        aoc23::main!(@day Normalized []);
+    };
+    (with_context $(, $(($($case:tt)*)),+)? $(,)?) => {
+        aoc23::main!(@day Normalized [with_context]);
+        $(test_with_example!(with_context, $(($($case)*)),+);)?
+    };
+    (raw_input $(,)?) => {
+        aoc23::main!(@day Raw []);
+    };
+    (raw_input, with_context $(, $(($($case:tt)*)),+)? $(,)?) => {
+        aoc23::main!(@day Raw [with_context]);
+        $(test_with_example!(with_context, $(($($case)*)),+);)?
+    };
+    (raw_input, $(($($case:tt)*)),+ $(,)?) => {
+        aoc23::main!(@day Raw []);
+        test_with_example!($(($($case)*)),+);
+    };
+    (@day $mode:ident [$($marker:ident)?]) => {
+        #[allow(dead_code)]
         fn main() -> anyhow::Result<()> {
             let args = aoc23::get_cli_args();
-            let parse_start = std::time::Instant::now();
-            let parsed = parse(include_str!("input.txt"))?;
-            let t_parse = parse_start.elapsed();
-            let mut t1 = None;
-            let mut t2 = None;
-            let mut p1 = || {
-                let now = std::time::Instant::now();
-                let p1_res = part1(&parsed);
-                t1 = Some(now.elapsed());
-                p1_res
-            };
-            let mut p2 = || {
-                let now = std::time::Instant::now();
-                let p2_res = part2(&parsed);
-                t2 = Some(now.elapsed());
-                p2_res
-            };
-            let (mut p1_res, mut p2_res) = match args.part {
-                Some(aoc23::Part::Part1) => (Some(p1()), None),
-                Some(aoc23::Part::Part2) => (None, Some(p2())),
-                None => (Some(p1()), Some(p2())),
-            };
-            aoc23::print_results(t_parse, p1_res, t1, p2_res, t2)
+            let input = aoc23::read_input(args.input.as_deref(), INPUT)?;
+            aoc23::print_report(aoc23::day_number(file!()), run(&input, &args), &args)
+        }
+
+        pub const INPUT: &str = include_str!("input.txt");
+
+        /// How the input is prepared before parsing, both for `run` and for the example tests
+        pub const INPUT_MODE: aoc23::InputMode = aoc23::InputMode::$mode;
+
+        pub fn run(input: &str, args: &aoc23::Args) -> anyhow::Result<aoc23::DayReport> {
+            let input = INPUT_MODE.prepare(input);
+            match args.timeout {
+                None => aoc23::run_solution(&aoc23::day_solution!(self $(, $marker)?), &input, args),
+                Some(timeout) => aoc23::run_solution_with_timeout(
+                    aoc23::day_solution!(self $(, $marker)?),
+                    &input,
+                    timeout,
+                    args,
+                ),
+            }
         }
     };
+    ($(($($case:tt)*)),+ $(,)?) => {
+        main!();
+        test_with_example!($(($($case)*)),+);
+    };
     ($part1_expected:expr, $part2_expected:expr) => {
         main!();
         test_with_example!($part1_expected, $part2_expected);
@@ -91,219 +232,138 @@ macro_rules! main {
     };
 }
 
+/// Generates one test per example case.
+///
+/// A case is `(file, part, expected)`, where `file` names a `.txt` file next to the day,
+/// optionally followed by `key = value` parameters for the [`Context`]:
+/// `(example, part1, 16, steps = 6)` generates the test `part1_example_steps_6`.
+/// Days whose parts take the context start the cases with `with_context`.
 #[macro_export]
+// `crate::day_tests` is the one of the crate including the day, see [`day_tests`]
+#[allow(clippy::crate_in_macro_def)]
 macro_rules! test_with_example {
+    ($(($file:ident, $part:ident, $expected:expr $(, $key:ident = $value:literal)* $(,)?)),+ $(,)?) => {
+        aoc23::test_with_example!(@cases [], $(($file, $part, $expected, [$($key = $value),*])),+);
+    };
+    (with_context, $(($file:ident, $part:ident, $expected:expr $(, $key:ident = $value:literal)* $(,)?)),+ $(,)?) => {
+        aoc23::test_with_example!(@cases [with_context], $(($file, $part, $expected, [$($key = $value),*])),+);
+    };
     ($part1_expected:expr, $part2_expected:expr) => {
-        #[cfg(test)]
-        mod tests {
-            #[test]
-            fn part1() -> anyhow::Result<()> {
-                let parsed = super::parse(include_str!("example.txt"))?;
-                let res = super::part1(&parsed)?;
-                assert_eq!(res, $part1_expected);
-                Ok(())
-            }
-            #[test]
-            fn part2() -> anyhow::Result<()> {
-                let parsed = super::parse(include_str!("example.txt"))?;
-                let res = super::part2(&parsed)?;
-                assert_eq!(res, $part2_expected);
-                Ok(())
+        test_with_example!((example, part1, $part1_expected), (example, part2, $part2_expected));
+    };
+    ($path1:literal, $part1_expected:expr, $path2:literal, $part2_expected:expr) => {
+        crate::day_tests! {
+            mod tests {
+                use aoc23::Solution;
+
+                aoc23::test_with_example!(@case [], part1, $path1, part1, $part1_expected, []);
+                aoc23::test_with_example!(@case [], part2, $path2, part2, $part2_expected, []);
             }
         }
     };
-    ($path1:literal, $part1_expected:expr, $path2:literal, $part2_expected:expr) => {
-        #[cfg(test)]
-        mod tests {
-            #[test]
-            fn part1() -> anyhow::Result<()> {
-                let parsed = super::parse(include_str!($path1))?;
-                let part1 = super::part1(&parsed)?;
-                assert_eq!(part1, $part1_expected);
-                Ok(())
+    (@cases $marker:tt, $(($file:ident, $part:ident, $expected:expr, [$($key:ident = $value:literal),*])),+) => {
+        crate::day_tests! {
+            mod tests {
+                use aoc23::Solution;
+
+                $(
+                    aoc23::test_with_example!(
+                        @case $marker,
+                        [<$part _ $file $(_ $key _ $value)*>],
+                        concat!(stringify!($file), ".txt"),
+                        $part,
+                        $expected,
+                        [$($key = $value),*]
+                    );
+                )+
             }
+        }
+    };
+    (@case [$($marker:ident)?], $name:tt, $path:expr, $part:ident, $expected:expr, [$($key:ident = $value:literal),*]) => {
+        aoc23::pastey::paste! {
             #[test]
-            fn part2() -> anyhow::Result<()> {
-                let parsed = super::parse(include_str!($path2))?;
-                let part2 = super::part2(&parsed)?;
-                assert_eq!(part2, $part2_expected);
+            fn $name() -> anyhow::Result<()> {
+                let solution = aoc23::day_solution!(super $(, $marker)?);
+                let input = super::INPUT_MODE.prepare(include_str!($path));
+                let ctx = aoc23::Context::default()$(.with_param(stringify!($key), $value))*;
+                let answer = aoc23::solve_example(&solution, $path, &input, &ctx, |s, parsed, ctx| {
+                    s.$part(parsed, ctx)
+                })?;
+                assert_eq!(answer, $expected, "wrong answer for {}", $path);
                 Ok(())
             }
         }
     };
 }
 
-#[derive(Error, Debug)]
-#[error("Failed to parse '{text}': {err}")]
-pub struct ParseError {
-    text: String,
-    err: String,
-}
-
-pub fn parse_error(text: &str, err: &str) -> ParseError {
-    ParseError {
-        text: String::from(text),
-        err: String::from(err),
-    }
+/// Compiles the tests of a day, called as `crate::day_tests! { mod test_x { ... } }` so that
+/// the `aoc` binary, which includes the days, leaves them out with its own `day_tests`: they run
+/// with the binary of each day.
+#[macro_export]
+macro_rules! day_tests {
+    ($($item:item)*) => {
+        $(
+            #[cfg(test)]
+            $item
+        )*
+    };
 }
 
-pub fn parse_collect<Item, T: FromIterator<Item>>(
-    s: &str,
-    delim: char,
-) -> Result<T, <Item as FromStr>::Err>
+/// Parses the non-empty pieces of `s` between the delimiters
+pub fn parse_collect<Item, T: FromIterator<Item>>(s: &str, delim: char) -> Result<T>
 where
     Item: FromStr,
+    Item::Err: Into<anyhow::Error>,
 {
     s.split(delim)
         .filter(|s| !s.is_empty())
-        .map(|st| st.parse())
+        .map(|st| st.parse().map_err(|err| ParseError::wrap(st, err)))
         .collect()
 }
 
-pub fn parse_collect_str<Item, T: FromIterator<Item>>(
-    s: &str,
-    delim: &str,
-) -> Result<T, <Item as FromStr>::Err>
+pub fn parse_collect_str<Item, T: FromIterator<Item>>(s: &str, delim: &str) -> Result<T>
 where
     Item: FromStr,
+    Item::Err: Into<anyhow::Error>,
 {
     s.split(delim)
         .filter(|s| !s.is_empty())
-        .map(|st| st.parse())
+        .map(|st| st.parse().map_err(|err| ParseError::wrap(st, err)))
         .collect()
 }
 
-pub fn collect_lines<Item, T: FromIterator<Item>>(s: &str) -> Result<T, <Item as FromStr>::Err>
+pub fn collect_lines<Item, T: FromIterator<Item>>(s: &str) -> Result<T>
 where
     Item: FromStr,
+    Item::Err: Into<anyhow::Error>,
 {
-    s.lines().map(|st| st.parse()).collect()
+    s.lines()
+        .map(|line| line.parse().map_err(|err| ParseError::wrap(line, err)))
+        .collect()
 }
 
-#[macro_export]
-macro_rules! set_field_ordered {
-    ($t:ident, $split:ident, $member:ident ()) => {{
-        let s = $split.next().expect("not enough delimiters");
-        $t.$member = s.parse()?;
-    }};
-    ($t:ident, $split:ident, $member:ident (trim)) => {{
-        let s = $split.next().expect("not enough delimiters");
-        $t.$member = s.trim().parse()?;
-    }};
-    ($t:ident, $split:ident, $member:ident (collect(remaining))) => {{
-        $t.$member = $split.map(|s| s.parse()).try_collect()?;
-    }};
-    ($t:ident, $split:ident, $member:ident (collect(lines))) => {{
-        let s = $split.next().expect("not enough delimiters");
-        $t.$member = s
-            .lines()
-            .filter(|s| !s.is_empty())
-            .map(|st| st.parse())
-            .try_collect()?;
-    }};
-    ($t:ident, $split:ident, $member:ident (collect($delim:literal))) => {{
-        let s = $split.next().expect("not enough delimiters");
-        $t.$member = aoc23::parse_collect(s, $delim)?;
-    }};
-    ($t:ident, $split:ident, $member:ident (re($regex:literal))) => {{
-        let s = $split.next().expect("not enough delimiters");
-        static RE: once_cell::sync::Lazy<regex::Regex> =
-            once_cell::sync::Lazy::new(|| regex::Regex::new($regex).unwrap());
-        if let Some(cap) = RE.captures(s) {
-            $t.$member = cap.get(1).unwrap().as_str().parse()?;
-        } else {
-            return Err(aoc23::parse_error(
-                s,
-                &format!("failed to match regex {}", $regex),
-            ))?;
-        }
-    }};
+/// Parses the lines of `s` as types that borrow from it
+pub fn collect_lines_borrowed<'a, Item, T>(s: &'a str) -> Result<T>
+where
+    Item: ParseBorrowed<'a>,
+    T: FromIterator<Item>,
+{
+    s.lines()
+        .map(|line| Item::parse(line).map_err(|err| ParseError::wrap(line, err)))
+        .collect()
 }
 
-#[macro_export]
-macro_rules! parse_ordered {(
-    #[delim($delim:expr)]
-    $(#[$struct_meta:meta])*
-    $struct_vis:vis
-    struct $StructName:ident {
-        $(
-            #[parse $field_parser:tt]
-            $(#[$field_meta:meta])*
-            $field_vis:vis
-            $field_name:ident : $field_ty:ty
-        ),* $(,)?
-    }
-) => (
-    // Generate the struct definition we have been given
-    $(#[$struct_meta])*
-    $struct_vis
-    struct $StructName {
-        $(
-            $(#[$field_meta])*
-            $field_vis $field_name: $field_ty,
-        )*
-    }
-    // Generate an implementation of FromStr
-    impl FromStr for $StructName {
-        type Err = anyhow::Error;
-
-        fn from_str(string: &str) -> Result<Self> {
-            let mut t = <$StructName>::default();
-            let mut split = string.split($delim);
-            $({
-                set_field_ordered!(t, split, $field_name $field_parser);
-            })*
-            Ok(t)
-        }
-    }
-)}
+#[cfg(test)]
+mod test_input {
+    use std::borrow::Cow;
 
-#[macro_export]
-macro_rules! set_matching_field {
-    ($t:ident, $s:ident, $member:ident ($regex:literal)) => {{
-        static RE: once_cell::sync::Lazy<regex::Regex> =
-            once_cell::sync::Lazy::new(|| regex::Regex::new($regex).unwrap());
-        if let Some(cap) = RE.captures($s) {
-            $t.$member = cap.get(1).unwrap().as_str().parse()?;
-            return Ok($t);
-        }
-    }};
-}
+    use crate::{normalize_input, InputMode};
 
-#[macro_export]
-macro_rules! parse_matching {(
-    #[delim($delim:literal)]
-    $(#[$struct_meta:meta])*
-    $struct_vis:vis
-    struct $StructName:ident {
-        $(
-            #[parse $field_parser:tt]
-            $(#[$field_meta:meta])*
-            $field_vis:vis // this visibility will be applied to the getters instead
-            $field_name:ident : $field_ty:ty
-        ),* $(,)?
+    #[test]
+    fn test_normalize_input() {
+        assert_eq!(normalize_input("\u{feff}a b\r\n\r\nc\r\n\r\n"), "a b\n\nc");
+        assert!(matches!(normalize_input("a\nb\n"), Cow::Borrowed("a\nb")));
+        assert_eq!(normalize_input("a\n  b \n\n"), "a\n  b ");
+        assert_eq!(InputMode::Raw.prepare("a\r\n"), "a\r\n");
     }
-) => (
-    // First, generate the struct definition we have been given
-    $(#[$struct_meta])*
-    $struct_vis
-    struct $StructName {
-        $(
-            $(#[$field_meta])*
-            $field_vis $field_name: $field_ty,
-        )*
-    }
-    impl FromStr for $StructName {
-        type Err = anyhow::Error;
-
-        fn from_str(string: &str) -> Result<Self> {
-            string.split($delim).try_fold(<$StructName>::default(), |mut t, s| {
-                $(
-                    set_matching_field!(t, s, $field_name $field_parser);
-                )*
-                #[allow(unreachable_code)]
-                Err(aoc23::parse_error(string, ""))?
-            })
-        }
-    }
-)}
+}
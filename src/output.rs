@@ -0,0 +1,345 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use crate::{
+    alloc::AllocStats,
+    answers::{check_failures, check_line, ANSWERS},
+    bench::Stats,
+    history,
+    runner::{print_results, print_summary, DayReport, PartReport},
+    Args, Part, Result,
+};
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Human readable results and timings
+    #[default]
+    Text,
+    /// One JSON object per part
+    Json,
+    /// One CSV row per part
+    Csv,
+}
+
+/// Result of one part of one day, in a form suitable for serialization
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Record {
+    pub day: u32,
+    /// `None` when the parse failed
+    pub part: Option<u8>,
+    /// `None` when the part failed or is unsolved
+    pub answer: Option<String>,
+    pub error: Option<String>,
+    /// solved, unsolved or error, so that an unsolved part is not mistaken for a missing answer
+    pub status: Option<&'static str>,
+    pub parse_time: Option<Duration>,
+    pub part_time: Option<Duration>,
+    pub bench_iterations: Option<usize>,
+    pub bench: Option<Stats>,
+    /// Allocations of the parse and of the part, with the `alloc-stats` feature
+    pub parse_alloc: Option<AllocStats>,
+    pub part_alloc: Option<AllocStats>,
+    /// PASS, FAIL or MISSING when the answers are checked
+    pub check: Option<&'static str>,
+    pub expected: Option<String>,
+}
+
+pub fn records(day: u32, report: &Result<DayReport>, check: bool) -> Vec<Record> {
+    match report {
+        Ok(report) => {
+            let parts = [(1, &report.part1), (2, &report.part2)];
+            parts
+                .into_iter()
+                .filter_map(|(part, p)| Some((part, p.as_ref()?)))
+                .map(|(part, p): (u8, &PartReport)| {
+                    let bench = report.bench.as_ref();
+                    let puzzle_part = if part == 1 { Part::Part1 } else { Part::Part2 };
+                    let status = check.then(|| ANSWERS.check(day, puzzle_part, p));
+                    let part_status = match &p.answer {
+                        Ok(answer) if answer.is_solved() => "solved",
+                        Ok(_) => "unsolved",
+                        Err(_) => "error",
+                    };
+                    Record {
+                        day,
+                        part: Some(part),
+                        answer: p
+                            .answer
+                            .as_ref()
+                            .ok()
+                            .filter(|answer| answer.is_solved())
+                            .map(ToString::to_string),
+                        error: p.answer.as_ref().err().map(|err| format!("{err:#}")),
+                        status: Some(part_status),
+                        parse_time: Some(report.parse_time),
+                        part_time: Some(p.time),
+                        parse_alloc: report.parse_alloc,
+                        part_alloc: p.alloc,
+                        bench_iterations: bench.map(|b| b.iterations),
+                        bench: bench.and_then(|b| if part == 1 { b.part1 } else { b.part2 }),
+                        check: status.as_ref().map(|s| s.name()),
+                        expected: status
+                            .and(ANSWERS.expected(day, puzzle_part))
+                            .map(String::from),
+                    }
+                })
+                .collect()
+        }
+        Err(err) => vec![Record {
+            day,
+            error: Some(format!("{err:#}")),
+            status: Some("error"),
+            check: check.then_some("FAIL"),
+            ..Default::default()
+        }],
+    }
+}
+
+/// Prints the report of a single day in the requested format
+pub fn print_report(day: u32, report: Result<DayReport>, args: &Args) -> Result<()> {
+    record_history(&[(day, report_ref(&report))], args)?;
+    match args.format {
+        Format::Text if args.check => {
+            let (line, failures) = check_line(day, &report);
+            print_results(report?)?;
+            println!("\nCheck:\n    {line}");
+            check_failures(failures)
+        }
+        Format::Text => print_results(report?),
+        format => {
+            let records = records(day, &report, args.check);
+            print_records(format, &records);
+            check_failures(count_failures(&records))
+        }
+    }
+}
+
+/// Prints the reports of several days in the requested format
+pub fn print_reports(reports: &[(u32, Result<DayReport>)], args: &Args) -> Result<()> {
+    let refs = reports
+        .iter()
+        .map(|(day, report)| (*day, report_ref(report)))
+        .collect::<Vec<_>>();
+    record_history(&refs, args)?;
+    match args.format {
+        Format::Text => {
+            print_summary(reports);
+            if !args.check {
+                return Ok(());
+            }
+            println!("\nCheck:");
+            let mut failures = 0;
+            for (day, report) in reports {
+                let (line, day_failures) = check_line(*day, report);
+                println!("    {line}");
+                failures += day_failures;
+            }
+            check_failures(failures)
+        }
+        format => {
+            let records = reports
+                .iter()
+                .flat_map(|(day, report)| records(*day, report, args.check))
+                .collect::<Vec<_>>();
+            print_records(format, &records);
+            check_failures(count_failures(&records))
+        }
+    }
+}
+
+fn report_ref(report: &Result<DayReport>) -> Option<&DayReport> {
+    report.as_ref().ok()
+}
+
+/// Appends the timings to the history file when a label was given
+fn record_history(reports: &[(u32, Option<&DayReport>)], args: &Args) -> Result<()> {
+    let Some(label) = &args.label else {
+        return Ok(());
+    };
+    let entries = reports
+        .iter()
+        .flat_map(|(day, report)| history::entries(label, *day, *report))
+        .collect::<Vec<_>>();
+    history::append(&history::history_path(args.history.as_deref()), &entries)
+}
+
+fn count_failures(records: &[Record]) -> usize {
+    records.iter().filter(|r| r.check == Some("FAIL")).count()
+}
+
+fn print_records(format: Format, records: &[Record]) {
+    match format {
+        Format::Text => unreachable!("text output is not record based"),
+        Format::Json => {
+            println!("[");
+            for (i, r) in records.iter().enumerate() {
+                let separator = if i + 1 < records.len() { "," } else { "" };
+                println!("  {}{separator}", r.to_json());
+            }
+            println!("]");
+        }
+        Format::Csv => {
+            let header = Record::default().fields().map(|(name, _)| name);
+            println!("{}", header.join(","));
+            for r in records {
+                println!("{}", r.to_csv());
+            }
+        }
+    }
+}
+
+impl Record {
+    fn fields(&self) -> [(&'static str, Value); 21] {
+        let nanos = |d: Option<Duration>| Value::Int(d.map(|d| d.as_nanos()));
+        let stat = |f: fn(&Stats) -> Duration| nanos(self.bench.as_ref().map(f));
+        let alloc =
+            |a: Option<AllocStats>, f: fn(AllocStats) -> u64| Value::Int(a.map(|a| f(a) as u128));
+        [
+            ("day", Value::Int(Some(self.day as u128))),
+            ("part", Value::Int(self.part.map(|p| p as u128))),
+            ("answer", Value::Str(self.answer.clone())),
+            ("error", Value::Str(self.error.clone())),
+            ("status", Value::Str(self.status.map(String::from))),
+            ("parse_ns", nanos(self.parse_time)),
+            ("part_ns", nanos(self.part_time)),
+            (
+                "bench_iterations",
+                Value::Int(self.bench_iterations.map(|n| n as u128)),
+            ),
+            ("min_ns", stat(|s| s.min)),
+            ("median_ns", stat(|s| s.median)),
+            ("mean_ns", stat(|s| s.mean)),
+            ("p95_ns", stat(|s| s.p95)),
+            ("std_dev_ns", stat(|s| s.std_dev)),
+            ("parse_allocs", alloc(self.parse_alloc, |a| a.count)),
+            ("parse_alloc_bytes", alloc(self.parse_alloc, |a| a.bytes)),
+            ("parse_peak_bytes", alloc(self.parse_alloc, |a| a.peak)),
+            ("part_allocs", alloc(self.part_alloc, |a| a.count)),
+            ("part_alloc_bytes", alloc(self.part_alloc, |a| a.bytes)),
+            ("part_peak_bytes", alloc(self.part_alloc, |a| a.peak)),
+            ("check", Value::Str(self.check.map(String::from))),
+            ("expected", Value::Str(self.expected.clone())),
+        ]
+    }
+
+    pub fn to_json(&self) -> String {
+        let fields = self
+            .fields()
+            .into_iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::Int(Some(n)) => n.to_string(),
+                    Value::Str(Some(s)) => json_string(&s),
+                    Value::Int(None) | Value::Str(None) => String::from("null"),
+                };
+                format!("\"{name}\": {value}")
+            })
+            .collect::<Vec<_>>();
+        format!("{{{}}}", fields.join(", "))
+    }
+
+    pub fn to_csv(&self) -> String {
+        self.fields()
+            .into_iter()
+            .map(|(_, value)| match value {
+                Value::Int(Some(n)) => n.to_string(),
+                Value::Str(Some(s)) => csv_field(&s),
+                Value::Int(None) | Value::Str(None) => String::new(),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+enum Value {
+    Int(Option<u128>),
+    Str(Option<String>),
+}
+
+fn json_string(s: &str) -> String {
+    let mut res = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        String::from(s)
+    }
+}
+
+#[cfg(test)]
+mod test_output {
+    use std::time::Duration;
+
+    use crate::{
+        output::{records, Record},
+        runner::{DayReport, PartReport},
+        Answer,
+    };
+
+    #[test]
+    fn test_serialize_record() {
+        let record = Record {
+            day: 7,
+            part: Some(2),
+            error: Some(String::from("bad \"hand\", line 3")),
+            status: Some("error"),
+            parse_time: Some(Duration::from_micros(12)),
+            part_time: Some(Duration::from_nanos(345)),
+            ..Default::default()
+        };
+        assert_eq!(
+            record.to_json(),
+            "{\"day\": 7, \"part\": 2, \"answer\": null, \"error\": \"bad \\\"hand\\\", line 3\", \
+             \"status\": \"error\", \"parse_ns\": 12000, \"part_ns\": 345, \
+             \"bench_iterations\": null, \"min_ns\": null, \
+             \"median_ns\": null, \"mean_ns\": null, \"p95_ns\": null, \"std_dev_ns\": null, \
+             \"parse_allocs\": null, \"parse_alloc_bytes\": null, \"parse_peak_bytes\": null, \
+             \"part_allocs\": null, \"part_alloc_bytes\": null, \"part_peak_bytes\": null, \
+             \"check\": null, \"expected\": null}"
+        );
+        assert_eq!(
+            record.to_csv(),
+            "7,2,,\"bad \"\"hand\"\", line 3\",error,12000,345,,,,,,,,,,,,,,"
+        );
+    }
+
+    #[test]
+    fn test_unsolved_record() {
+        let part = |answer| PartReport {
+            answer: Ok(answer),
+            time: Duration::ZERO,
+            alloc: None,
+        };
+        let report = DayReport {
+            parse_time: Duration::ZERO,
+            parse_alloc: None,
+            part1: Some(part(Answer::from(42u32))),
+            part2: Some(part(Answer::Unsolved)),
+            bench: None,
+        };
+        let records = records(24, &Ok(report), false);
+        assert_eq!(records[0].answer.as_deref(), Some("42"));
+        assert_eq!(records[0].status, Some("solved"));
+        assert_eq!(records[1].answer, None);
+        assert_eq!(records[1].status, Some("unsolved"));
+        assert!(records[1]
+            .to_json()
+            .contains("\"answer\": null, \"error\": null, \"status\": \"unsolved\""));
+    }
+}
@@ -0,0 +1,348 @@
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use crate::{ParseError, Result};
+
+/// Parser of the start of a string, giving the parsed value and the rest of the string.
+///
+/// The errors are [`ParseError`]s about slices of the parsed string, so they are located in the
+/// input like the other parse errors. Any `Fn(&str) -> Result<(T, &str)>` is a parser.
+pub trait Parser<'a, T>: Sized {
+    fn parse_prefix(&self, s: &'a str) -> Result<(T, &'a str)>;
+
+    /// Parses the whole string
+    fn parse_all(&self, s: &'a str) -> Result<T> {
+        match self.parse_prefix(s)? {
+            (value, "") => Ok(value),
+            (_, rest) => Err(expected(rest, "the end of the text")),
+        }
+    }
+
+    fn map<U>(self, f: impl Fn(T) -> U) -> impl Parser<'a, U> {
+        move |s: &'a str| {
+            let (value, rest) = self.parse_prefix(s)?;
+            Ok((f(value), rest))
+        }
+    }
+
+    /// Converts the value with `f`, whose errors are about the parsed text
+    fn try_map<U, E>(self, f: impl Fn(T) -> Result<U, E>) -> impl Parser<'a, U>
+    where
+        E: Into<anyhow::Error>,
+    {
+        move |s: &'a str| {
+            let (value, rest) = self.parse_prefix(s)?;
+            let parsed = &s[..s.len() - rest.len()];
+            let value = f(value).map_err(|err| ParseError::wrap(parsed, err))?;
+            Ok((value, rest))
+        }
+    }
+
+    /// Parses `next` after this one, giving both values
+    fn then<U>(self, next: impl Parser<'a, U>) -> impl Parser<'a, (T, U)> {
+        move |s: &'a str| {
+            let (value, rest) = self.parse_prefix(s)?;
+            let (next_value, rest) = next.parse_prefix(rest)?;
+            Ok(((value, next_value), rest))
+        }
+    }
+
+    /// Parses `next` after this one, keeping only this value
+    fn skip<U>(self, next: impl Parser<'a, U>) -> impl Parser<'a, T> {
+        self.then(next).map(|(value, _)| value)
+    }
+}
+
+impl<'a, T, F> Parser<'a, T> for F
+where
+    F: Fn(&'a str) -> Result<(T, &'a str)>,
+{
+    fn parse_prefix(&self, s: &'a str) -> Result<(T, &'a str)> {
+        self(s)
+    }
+}
+
+/// Error of `s` not starting with `what`, about its first character
+fn expected(s: &str, what: &str) -> anyhow::Error {
+    let next = s.chars().next().map_or(0, char::len_utf8);
+    ParseError::mismatch(&s[..next], format!("expected {what}")).into()
+}
+
+/// Whether `err` is a parser matching none of `s`, rather than failing after its start or
+/// failing to convert what it matched
+fn mismatched_at(err: &anyhow::Error, s: &str) -> bool {
+    err.downcast_ref::<ParseError>()
+        .is_some_and(|err| err.is_mismatch_at(s))
+}
+
+/// The longest non-empty prefix of characters matching `pred`
+fn take_while<'a>(what: &'static str, pred: impl Fn(char) -> bool) -> impl Parser<'a, &'a str> {
+    move |s: &'a str| match s.find(|c| !pred(c)).unwrap_or(s.len()) {
+        0 => Err(expected(s, what)),
+        end => Ok(s.split_at(end)),
+    }
+}
+
+/// The string `tag`
+pub fn tag<'a>(tag: &'static str) -> impl Parser<'a, &'a str> {
+    move |s: &'a str| match s.strip_prefix(tag) {
+        Some(rest) => Ok((&s[..tag.len()], rest)),
+        None => Err(expected(s, &format!("{tag:?}"))),
+    }
+}
+
+/// The value of the first of the strings that the string starts with
+pub fn one_of<'a, T: Clone, const N: usize>(choices: [(&'static str, T); N]) -> impl Parser<'a, T> {
+    move |s: &'a str| {
+        choices
+            .iter()
+            .find_map(|(tag, value)| Some((value.clone(), s.strip_prefix(tag)?)))
+            .ok_or_else(|| {
+                let tags = choices.iter().map(|(tag, _)| format!("{tag:?}")).join(", ");
+                expected(s, &format!("one of {tags}"))
+            })
+    }
+}
+
+/// The next `n` characters
+pub fn take<'a>(n: usize) -> impl Parser<'a, &'a str> {
+    move |s: &'a str| match s.char_indices().map(|(i, _)| i).chain([s.len()]).nth(n) {
+        Some(end) => Ok(s.split_at(end)),
+        None => Err(expected(&s[s.len()..], &format!("{n} characters"))),
+    }
+}
+
+/// Decimal digits
+pub fn digits<'a>() -> impl Parser<'a, &'a str> {
+    take_while("digits", |c| c.is_ascii_digit())
+}
+
+/// Unsigned integer
+pub fn uint<'a, T>() -> impl Parser<'a, T>
+where
+    T: FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    digits().try_map(str::parse)
+}
+
+/// Integer with an optional sign
+pub fn int<'a, T>() -> impl Parser<'a, T>
+where
+    T: FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    move |s: &'a str| {
+        let (_, rest) = digits().parse_prefix(s.strip_prefix(['-', '+']).unwrap_or(s))?;
+        let number = &s[..s.len() - rest.len()];
+        let value = number
+            .parse()
+            .map_err(|err| ParseError::wrap(number, err))?;
+        Ok((value, rest))
+    }
+}
+
+/// Letters, digits and underscores
+pub fn word<'a>() -> impl Parser<'a, &'a str> {
+    take_while("a word", |c| c.is_alphanumeric() || c == '_')
+}
+
+/// Spaces and tabs
+pub fn spaces<'a>() -> impl Parser<'a, &'a str> {
+    take_while("spaces", |c| c == ' ' || c == '\t')
+}
+
+/// Optional spaces and tabs, possibly none
+pub fn spaces0<'a>() -> impl Parser<'a, &'a str> {
+    |s: &'a str| {
+        let rest = s.trim_start_matches([' ', '\t']);
+        Ok(s.split_at(s.len() - rest.len()))
+    }
+}
+
+/// `parser` after optional spaces and tabs
+pub fn spaced<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    spaces0().then(parser).map(|(_, value)| value)
+}
+
+/// `parser` after the string `prefix`
+pub fn prefixed<'a, T>(prefix: &'static str, parser: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    tag(prefix).then(parser).map(|(_, value)| value)
+}
+
+/// `parser` between the strings `open` and `close`, as in `(a, b)`
+pub fn bracketed<'a, T>(
+    open: &'static str,
+    parser: impl Parser<'a, T>,
+    close: &'static str,
+) -> impl Parser<'a, T> {
+    prefixed(open, parser).skip(tag(close))
+}
+
+/// `key`, the string `separator`, then `value`, as in `a = 1`
+pub fn key_value<'a, K, V>(
+    key: impl Parser<'a, K>,
+    separator: &'static str,
+    value: impl Parser<'a, V>,
+) -> impl Parser<'a, (K, V)> {
+    key.skip(tag(separator)).then(value)
+}
+
+/// One or more items between separators.
+///
+/// A separator that is not followed by an item is left in the rest of the string, as the trailing
+/// spaces of `"Time: 7 15 "` with spaces between the items. An item that starts after it but
+/// fails to parse is an error.
+pub fn separated<'a, T, U, C>(
+    item: impl Parser<'a, T>,
+    separator: impl Parser<'a, U>,
+) -> impl Parser<'a, C>
+where
+    C: FromIterator<T>,
+{
+    move |s: &'a str| {
+        let (first, mut rest) = item.parse_prefix(s)?;
+        let mut items = vec![first];
+        while let Ok((_, after)) = separator.parse_prefix(rest) {
+            match item.parse_prefix(after) {
+                Ok((value, after)) => {
+                    items.push(value);
+                    rest = after;
+                }
+                Err(err) if mismatched_at(&err, after) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok((items.into_iter().collect(), rest))
+    }
+}
+
+/// Zero or more items, as long as `item` parses
+pub fn many<'a, T, C>(item: impl Parser<'a, T>) -> impl Parser<'a, C>
+where
+    C: FromIterator<T>,
+{
+    move |s: &'a str| {
+        let mut items = Vec::new();
+        let mut rest = s;
+        while let Ok((value, after)) = item.parse_prefix(rest) {
+            if after.len() == rest.len() {
+                break;
+            }
+            items.push(value);
+            rest = after;
+        }
+        Ok((items.into_iter().collect(), rest))
+    }
+}
+
+/// All the remaining lines, each parsed whole by `line`
+pub fn lines<'a, T, C>(line: impl Parser<'a, T>) -> impl Parser<'a, C>
+where
+    C: FromIterator<T>,
+{
+    move |s: &'a str| {
+        let items = s.lines().map(|l| line.parse_all(l)).try_collect()?;
+        Ok((items, &s[s.len()..]))
+    }
+}
+
+/// All the remaining sections between blank lines, each parsed whole by `section`
+pub fn sections<'a, T, C>(section: impl Parser<'a, T>) -> impl Parser<'a, C>
+where
+    C: FromIterator<T>,
+{
+    move |s: &'a str| {
+        let items = s
+            .split("\n\n")
+            .map(|l| section.parse_all(l))
+            .try_collect()?;
+        Ok((items, &s[s.len()..]))
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::locate_error;
+
+    #[test]
+    fn test_parsers() {
+        assert_eq!(uint::<u32>().parse_prefix("42 a").unwrap(), (42, " a"));
+        assert_eq!(int::<i64>().parse_all("-7").unwrap(), -7);
+        assert_eq!(
+            word().then(spaced(word())).parse_all("ab  c1").unwrap(),
+            ("ab", "c1")
+        );
+        assert_eq!(take(2).parse_prefix("éab").unwrap(), ("éa", "b"));
+        assert_eq!(spaces0().parse_prefix(" \tx").unwrap(), (" \t", "x"));
+        assert_eq!(spaces0().parse_prefix("x").unwrap(), ("", "x"));
+        assert_eq!(
+            bracketed("(", separated::<_, _, Vec<u32>>(uint(), tag(", ")), ")")
+                .parse_all("(1, 2, 3)")
+                .unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            separated::<_, _, Vec<u32>>(uint(), spaces())
+                .parse_prefix("7 15  ")
+                .unwrap(),
+            (vec![7, 15], "  ")
+        );
+        assert_eq!(
+            many(one_of([("L", 0), ("R", 1)]))
+                .parse_prefix("LRLx")
+                .unwrap(),
+            (vec![0, 1, 0], "x")
+        );
+        let sections: Vec<HashMap<&str, i32>> = sections(lines(key_value(word(), "=", int())))
+            .parse_all("a=1\nb=-2\n\nc=3")
+            .unwrap();
+        assert_eq!(sections[0]["b"], -2);
+        assert_eq!(sections[1]["c"], 3);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |res: Result<Vec<u8>>| format!("{:#}", res.unwrap_err());
+        let list = || separated(uint::<u8>(), tag(","));
+        assert_eq!(
+            err(list().parse_all("1,x")),
+            "Failed to parse ',': expected the end of the text"
+        );
+        assert_eq!(
+            err(list().parse_all("1;2")),
+            "Failed to parse ';': expected the end of the text"
+        );
+        assert_eq!(
+            err(list().parse_all("300,1")),
+            "Failed to parse '300': number too large to fit in target type"
+        );
+        assert_eq!(
+            err(list().parse_all("1,300")),
+            "Failed to parse '300': number too large to fit in target type"
+        );
+        assert_eq!(
+            format!("{:#}", one_of([("a", 1)]).parse_all("b").unwrap_err()),
+            "Failed to parse 'b': expected one of \"a\""
+        );
+
+        let input = "1 2\n3 x4";
+        let res = lines(separated(uint::<u8>(), spaces())).parse_all(input);
+        let err = locate_error(
+            res.map(|_: Vec<Vec<_>>| ()).unwrap_err(),
+            "input.txt",
+            input,
+        );
+        assert_eq!(
+            err.to_string(),
+            "input.txt:2:2: expected the end of the text\n  \
+               |\n\
+             2 | 3 x4\n  \
+               |  ^"
+        );
+    }
+}
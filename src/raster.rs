@@ -0,0 +1,516 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, Context as _};
+
+use crate::{grid::Grid, Result, Visualizer};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(0xFF, 0xFF, 0xFF);
+}
+
+/// RGB image, stored row by row from the top left corner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// Draws each cell of the grid as a `cell_size` square of a single color
+    pub fn from_grid<T>(grid: &Grid<T>, cell_size: usize, color: impl Fn(&T) -> Rgb) -> Image {
+        Image::from_grid_with(grid, cell_size, |cell, _| color(cell))
+    }
+
+    /// Draws each cell of the grid as a `cell_size` square, colored pixel by pixel.
+    ///
+    /// `color` gets the cell and the coordinates of the pixel within the cell, so that cells
+    /// can be drawn as small glyphs.
+    pub fn from_grid_with<T>(
+        grid: &Grid<T>,
+        cell_size: usize,
+        color: impl Fn(&T, (usize, usize)) -> Rgb,
+    ) -> Image {
+        let mut image = Image::new(grid.width * cell_size, grid.height * cell_size, Rgb::BLACK);
+        for ((x, y), cell) in grid.indexed_iter() {
+            image.draw_cell((x, y), cell_size, |pixel| color(cell, pixel));
+        }
+        image
+    }
+
+    /// Redraws the cell at `(x, y)` of an image made with [`Image::from_grid_with`]
+    pub fn draw_cell(
+        &mut self,
+        (x, y): (usize, usize),
+        cell_size: usize,
+        color: impl Fn((usize, usize)) -> Rgb,
+    ) {
+        for dy in 0..cell_size {
+            for dx in 0..cell_size {
+                let (px, py) = (x * cell_size + dx, y * cell_size + dy);
+                self.pixels[py * self.width + px] = color((dx, dy));
+            }
+        }
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(self.height * (1 + 3 * self.width));
+        for row in self.pixels.chunks(self.width.max(1)) {
+            // filter type None
+            raw.push(0);
+            raw.extend(row.iter().flat_map(|&Rgb(r, g, b)| [r, g, b]));
+        }
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bits per channel, truecolor, default compression, filter and interlacing
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut png, b"IHDR", &header);
+        png_chunk(
+            &mut png,
+            b"IDAT",
+            &miniz_oxide::deflate::compress_to_vec_zlib(&raw, 6),
+        );
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_file(path.as_ref(), &self.to_png())
+    }
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    std::fs::write(path, bytes).with_context(|| format!("failed to write '{}'", path.display()))
+}
+
+/// Animated GIF of images of the same size, with at most 256 distinct colors overall.
+///
+/// Each frame only stores the rectangle that changed since the previous one.
+pub struct Animation {
+    width: usize,
+    height: usize,
+    /// Delay between frames, in hundredths of a second
+    delay: u16,
+    palette: Vec<Rgb>,
+    indices: HashMap<Rgb, u8>,
+    previous: Option<Image>,
+    /// Encoded image blocks
+    frames: Vec<u8>,
+    frame_count: usize,
+}
+
+impl Animation {
+    pub fn new(width: usize, height: usize, delay_cs: u16) -> Animation {
+        Animation {
+            width,
+            height,
+            delay: delay_cs,
+            palette: Vec::new(),
+            indices: HashMap::new(),
+            previous: None,
+            frames: Vec::new(),
+            frame_count: 0,
+        }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    pub fn push_frame(&mut self, image: &Image) -> Result<()> {
+        if (image.width, image.height) != (self.width, self.height) {
+            bail!(
+                "frame of {}x{} in an animation of {}x{}",
+                image.width,
+                image.height,
+                self.width,
+                self.height
+            );
+        }
+        if self.width > u16::MAX as usize || self.height > u16::MAX as usize {
+            bail!("a GIF is at most {0}x{0}", u16::MAX);
+        }
+        let (left, top, right, bottom) = match &self.previous {
+            None => (0, 0, self.width, self.height),
+            // GIF frames cannot be empty: repeat the top left pixel when nothing changed
+            Some(previous) => {
+                changed_rect(&previous.pixels, &image.pixels, self.width).unwrap_or((0, 0, 1, 1))
+            }
+        };
+        let width = self.width;
+        let mut last = None;
+        let pixels = (top..bottom)
+            .flat_map(|y| &image.pixels[y * width + left..y * width + right])
+            .map(|&color| match last {
+                // most neighboring pixels share their color, skip hashing them
+                Some((last_color, index)) if last_color == color => Ok(index),
+                _ => {
+                    let index = self.palette_index(color)?;
+                    last = Some((color, index));
+                    Ok(index)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let min_code_size = self.color_table_bits().max(2);
+        let out = &mut self.frames;
+        // graphic control extension: no disposal, so the next frames draw over this one
+        out.extend([0x21, 0xF9, 0x04, 0x04]);
+        out.extend(self.delay.to_le_bytes());
+        out.extend([0x00, 0x00]);
+        // image descriptor, without local color table
+        out.push(0x2C);
+        for value in [left, top, right - left, bottom - top] {
+            out.extend((value as u16).to_le_bytes());
+        }
+        out.push(0x00);
+        out.push(min_code_size);
+        for block in lzw_encode(min_code_size, &pixels).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend(block);
+        }
+        out.push(0x00);
+
+        match &mut self.previous {
+            Some(previous) => {
+                for y in top..bottom {
+                    let row = y * width + left..y * width + right;
+                    previous.pixels[row.clone()].copy_from_slice(&image.pixels[row]);
+                }
+            }
+            None => self.previous = Some(image.clone()),
+        }
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    fn palette_index(&mut self, color: Rgb) -> Result<u8> {
+        if let Some(&index) = self.indices.get(&color) {
+            return Ok(index);
+        }
+        if self.palette.len() == 256 {
+            bail!("a GIF animation has at most 256 colors");
+        }
+        let index = self.palette.len() as u8;
+        self.palette.push(color);
+        self.indices.insert(color, index);
+        Ok(index)
+    }
+
+    /// Bits per index of the global color table, which has `2^bits` entries
+    fn color_table_bits(&self) -> u8 {
+        let mut bits = 1;
+        while (1 << bits) < self.palette.len() {
+            bits += 1;
+        }
+        bits
+    }
+
+    pub fn to_gif(&self) -> Vec<u8> {
+        let bits = self.color_table_bits();
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend((self.width as u16).to_le_bytes());
+        gif.extend((self.height as u16).to_le_bytes());
+        // global color table of 2^bits entries, 8 bits per channel
+        gif.extend([0xF0 | (bits - 1), 0, 0]);
+        for i in 0..1 << bits {
+            let Rgb(r, g, b) = self.palette.get(i).copied().unwrap_or_default();
+            gif.extend([r, g, b]);
+        }
+        // loop forever
+        gif.extend([0x21, 0xFF, 0x0B]);
+        gif.extend(b"NETSCAPE2.0");
+        gif.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+        gif.extend(&self.frames);
+        gif.push(0x3B);
+        gif
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_file(path.as_ref(), &self.to_gif())
+    }
+}
+
+/// Records the frames drawn as images into a GIF, and the last one into a PNG next to it, for
+/// `--record`. The text frames are skipped.
+pub struct GifVisualizer {
+    path: PathBuf,
+    /// In hundredths of a second
+    delay_cs: u16,
+    animation: Option<Animation>,
+    last: Option<Image>,
+}
+
+impl GifVisualizer {
+    pub fn new(path: impl Into<PathBuf>, delay: Duration) -> Self {
+        GifVisualizer {
+            path: path.into(),
+            delay_cs: (delay.as_millis() / 10).clamp(1, u16::MAX as u128) as u16,
+            animation: None,
+            last: None,
+        }
+    }
+}
+
+impl Visualizer for GifVisualizer {
+    fn frame(&mut self, _frame: &dyn Display) -> Result<()> {
+        Ok(())
+    }
+
+    fn image(&mut self, image: &Image) -> Result<()> {
+        self.animation
+            .get_or_insert_with(|| Animation::new(image.width, image.height, self.delay_cs))
+            .push_frame(image)?;
+        self.last = Some(image.clone());
+        Ok(())
+    }
+
+    /// Writes the files, if any image was drawn
+    fn end(&mut self) -> Result<()> {
+        if let (Some(animation), Some(last)) = (self.animation.take(), self.last.take()) {
+            animation.save(&self.path)?;
+            last.save_png(self.path.with_extension("png"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Smallest `(left, top, right, bottom)` rectangle containing the pixels that differ
+fn changed_rect<T: PartialEq>(
+    a: &[T],
+    b: &[T],
+    width: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let mut rect: Option<(usize, usize, usize, usize)> = None;
+    for (y, (row_a, row_b)) in a.chunks(width).zip(b.chunks(width)).enumerate() {
+        if row_a == row_b {
+            continue;
+        }
+        let differs = |x: &usize| row_a[*x] != row_b[*x];
+        let left = (0..width).find(differs).unwrap_or(0);
+        let right = (0..width).rfind(differs).map_or(width, |x| x + 1);
+        rect = Some(match rect {
+            None => (left, y, right, y + 1),
+            Some((l, t, r, _)) => (l.min(left), t, r.max(right), y + 1),
+        });
+    }
+    rect
+}
+
+/// Variable length LZW compression of GIF image data
+fn lzw_encode(min_code_size: u8, indices: &[u8]) -> Vec<u8> {
+    const MAX_CODE: u16 = 4096;
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut out = BitWriter::default();
+    let mut code_size = min_code_size + 1;
+    let mut next_code = end + 1;
+    // code of each prefix followed by each index, 0 when not in the dictionary yet
+    let alphabet = clear as usize;
+    let mut dict = vec![0u16; MAX_CODE as usize * alphabet];
+
+    out.write(clear, code_size);
+    let Some((&first, rest)) = indices.split_first() else {
+        out.write(end, code_size);
+        return out.finish();
+    };
+    let mut prefix = first as u16;
+    for &index in rest {
+        let entry = prefix as usize * alphabet + index as usize;
+        if dict[entry] != 0 {
+            prefix = dict[entry];
+            continue;
+        }
+        out.write(prefix, code_size);
+        if next_code == MAX_CODE {
+            out.write(clear, code_size);
+            dict.fill(0);
+            code_size = min_code_size + 1;
+            next_code = end + 1;
+        } else {
+            if next_code >= 1 << code_size {
+                code_size += 1;
+            }
+            dict[entry] = next_code;
+            next_code += 1;
+        }
+        prefix = index as u16;
+    }
+    out.write(prefix, code_size);
+    // the decoder adds an entry after reading the last code, which can widen the end code
+    if next_code >= 1 << code_size && code_size < 12 {
+        code_size += 1;
+    }
+    out.write(end, code_size);
+    out.finish()
+}
+
+/// Packs codes least significant bit first
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod test_raster {
+    use crate::{
+        grid::Grid,
+        raster::{changed_rect, crc32, lzw_encode, GifVisualizer, Image, Rgb},
+        Visualizer,
+    };
+
+    #[test]
+    fn test_png() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        let grid = Grid::<u8>::from_lines("ab\ncd");
+        let image = Image::from_grid(&grid, 2, |&c| Rgb(c, 0, 0));
+        assert_eq!((image.width, image.height), (4, 4));
+        assert_eq!(image.pixels[3], Rgb(b'b', 0, 0));
+        assert_eq!(image.pixels[8], Rgb(b'c', 0, 0));
+        let png = image.to_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xAE\x42\x60\x82");
+    }
+
+    #[test]
+    fn test_gif_encoding() {
+        // clear, 1, 11, 11 and end, widened to 4 bits once the dictionary reaches 8 codes
+        assert_eq!(lzw_encode(2, &[1, 1, 1, 1, 1]), [0x8C, 0x5D]);
+        assert_eq!(
+            changed_rect(&[0, 0, 0, 0, 0, 0], &[0, 0, 0, 0, 1, 0], 3),
+            Some((1, 1, 2, 2))
+        );
+        assert_eq!(changed_rect(&[0, 1], &[0, 1], 2), None);
+    }
+
+    /// Walks the blocks of a GIF, failing on a malformed one, and counts its images
+    fn gif_images(gif: &[u8]) -> usize {
+        // skips data sub-blocks, up to the empty one
+        let skip_blocks = |mut i: usize| {
+            while gif[i] != 0 {
+                i += 1 + gif[i] as usize;
+            }
+            i + 1
+        };
+        assert_eq!(&gif[..6], b"GIF89a");
+        let mut i = 13 + 3 * (2 << (gif[10] & 0x07));
+        let mut images = 0;
+        loop {
+            match gif[i] {
+                0x21 => i = skip_blocks(i + 2),
+                0x2C => {
+                    images += 1;
+                    assert_eq!(gif[i + 9] & 0x80, 0, "no local color table");
+                    i = skip_blocks(i + 11);
+                }
+                0x3B => break,
+                block => panic!("unexpected block {block:#x} at {i}"),
+            }
+        }
+        assert_eq!(i + 1, gif.len());
+        images
+    }
+
+    /// Walks the chunks of a PNG, checking their CRC, and gives its size
+    fn png_size(png: &[u8]) -> (u32, u32) {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let be = |i: usize| u32::from_be_bytes(png[i..i + 4].try_into().unwrap());
+        let mut i = 8;
+        let mut kinds = Vec::new();
+        while i < png.len() {
+            let len = be(i) as usize;
+            let chunk = &png[i + 4..i + 8 + len];
+            assert_eq!(crc32(chunk), be(i + 8 + len));
+            kinds.push(&chunk[..4]);
+            i += 12 + len;
+        }
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+        (be(16), be(20))
+    }
+
+    #[test]
+    fn test_gif_visualizer() {
+        let path = std::env::temp_dir().join(format!("aoc23-test-{}.gif", std::process::id()));
+        let mut visualizer = GifVisualizer::new(&path, std::time::Duration::from_millis(20));
+        let mut image = Image::new(3, 2, Rgb::BLACK);
+        visualizer.begin().unwrap();
+        visualizer.frame(&"skipped").unwrap();
+        visualizer.image(&image).unwrap();
+        image.pixels[4] = Rgb::WHITE;
+        visualizer.image(&image).unwrap();
+        visualizer.end().unwrap();
+
+        let gif = std::fs::read(&path).unwrap();
+        let png = std::fs::read(path.with_extension("png")).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(path.with_extension("png")).unwrap();
+        assert_eq!(gif_images(&gif), 2);
+        assert_eq!((gif[6], gif[8]), (3, 2));
+        assert_eq!(png_size(&png), (3, 2));
+    }
+}
@@ -0,0 +1,482 @@
+use std::{
+    any::Any,
+    cmp::Reverse,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context as _};
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::{
+    alloc::{self, AllocStats},
+    bench::{bench_solution, print_bench, BenchReport},
+    capture::{capture, print_captured},
+    locate_error,
+    raster::GifVisualizer,
+    Answer, Args, Context, Part, Result, Solution, TerminalVisualizer,
+};
+
+/// Entry of the day registry used by the `aoc` runner
+pub struct Day {
+    pub number: u32,
+    pub input: &'static str,
+    pub run: fn(&str, &Args) -> Result<DayReport>,
+}
+
+/// Extracts the day number from the path of its source file (eg `src/day5/main.rs`)
+pub fn day_number(path: &str) -> u32 {
+    path.rsplit(['/', '\\'])
+        .find_map(|component| component.strip_prefix("day")?.parse().ok())
+        .unwrap_or(0)
+}
+
+pub struct PartReport {
+    pub answer: Result<Answer>,
+    pub time: Duration,
+    /// `None` unless the `alloc-stats` feature is enabled
+    pub alloc: Option<AllocStats>,
+}
+
+/// Error of a part that did not finish within `--timeout`
+#[derive(Error, Debug)]
+#[error("timed out after {0:?}")]
+pub struct Timeout(pub Duration);
+
+pub struct DayReport {
+    pub parse_time: Duration,
+    pub parse_alloc: Option<AllocStats>,
+    pub part1: Option<PartReport>,
+    pub part2: Option<PartReport>,
+    pub bench: Option<BenchReport>,
+}
+
+/// Parses the input then runs the selected parts of the solution, timing each step.
+///
+/// The parts are then benchmarked if requested in `args`. They run on this thread, without a
+/// timeout, see [`run_solution_with_timeout`].
+pub fn run_solution<'a, S: Solution<'a>>(
+    solution: &S,
+    input: &'a str,
+    args: &Args,
+) -> Result<DayReport> {
+    run_day(solution, input, args, |parsed, ctx| {
+        let part1 = is_selected(args, Part::Part1).then(|| timed(|| solution.part1(&parsed, ctx)));
+        let part2 = is_selected(args, Part::Part2).then(|| timed(|| solution.part2(&parsed, ctx)));
+        (part1, part2)
+    })
+}
+
+/// Like [`run_solution`], but each part runs on a worker thread and is reported as timed out if
+/// it does not finish within `timeout`.
+///
+/// A part that times out is cancelled but not waited for: it keeps running in the background
+/// until it checks [`Context::check_cancelled`] or the process exits. This is why its worker
+/// owns a share of the solution and of the parsed input, and why the input is leaked.
+pub fn run_solution_with_timeout<S>(
+    solution: S,
+    input: &str,
+    timeout: Duration,
+    args: &Args,
+) -> Result<DayReport>
+where
+    S: Solution<'static> + Send + Sync + 'static,
+    S::Input: Send + Sync + 'static,
+{
+    let input: &'static str = Box::leak(input.into());
+    let solution = Arc::new(solution);
+    run_day(&*solution, input, args, |parsed, ctx| {
+        let parsed = Arc::new(parsed);
+        let part1 = is_selected(args, Part::Part1).then(|| {
+            let (solution, parsed) = (Arc::clone(&solution), Arc::clone(&parsed));
+            run_detached(timeout, ctx, move |ctx| solution.part1(&parsed, ctx))
+        });
+        let part2 = is_selected(args, Part::Part2).then(|| {
+            let (solution, parsed) = (Arc::clone(&solution), Arc::clone(&parsed));
+            run_detached(timeout, ctx, move |ctx| solution.part2(&parsed, ctx))
+        });
+        (part1, part2)
+    })
+}
+
+/// Parses the input, runs the parts with `run_parts`, then benchmarks them if requested
+fn run_day<'a, S: Solution<'a>>(
+    solution: &S,
+    input: &'a str,
+    args: &Args,
+    run_parts: impl FnOnce(S::Input, &Context) -> (Option<PartReport>, Option<PartReport>),
+) -> Result<DayReport> {
+    let mut ctx = Context::new(&args.params);
+    let delay = Duration::from_millis(args.frame_delay);
+    if args.visualize {
+        ctx = ctx.with_visualizer(TerminalVisualizer::new(delay));
+    } else if let Some(path) = &args.record {
+        ctx = ctx.with_visualizer(GifVisualizer::new(path, delay));
+    }
+    let parse_start = Instant::now();
+    let (parsed, parse_alloc) = alloc::measure(|| solution.parse(input));
+    let parse_time = parse_start.elapsed();
+    let file = args.input.as_ref().map_or_else(
+        || String::from("input.txt"),
+        |path| path.display().to_string(),
+    );
+    let parsed = parsed.map_err(|err| locate_error(err, &file, input))?;
+    ctx.begin_visualization()?;
+    let (part1, part2) = run_parts(parsed, &ctx);
+    ctx.end_visualization()?;
+    let failed = [&part1, &part2]
+        .into_iter()
+        .flatten()
+        .any(|p| p.answer.is_err());
+    let bench = match args.bench {
+        Some(iterations) if !failed => Some(bench_solution(
+            solution,
+            input,
+            &ctx,
+            args.part,
+            iterations,
+            args.reuse_input,
+        )?),
+        _ => None,
+    };
+    Ok(DayReport {
+        parse_time,
+        parse_alloc,
+        part1,
+        part2,
+        bench,
+    })
+}
+
+fn is_selected(args: &Args, part: Part) -> bool {
+    args.part.is_none_or(|selected| selected == part)
+}
+
+/// Parses an example then solves one part of it, for the tests generated by `test_with_example!`
+pub fn solve_example<'a, S: Solution<'a>, T>(
+    solution: &S,
+    file: &str,
+    input: &'a str,
+    ctx: &Context,
+    part: impl FnOnce(&S, &S::Input, &Context) -> Result<T>,
+) -> Result<T> {
+    let parsed = solution
+        .parse(input)
+        .map_err(|err| locate_error(err, file, input))
+        .with_context(|| format!("failed to parse {file}"))?;
+    part(solution, &parsed, ctx).with_context(|| format!("failed to solve {file}"))
+}
+
+/// Runs a part on a detached worker thread, waiting at most `timeout` for it.
+///
+/// A part that times out is cancelled and reported right away, a part that panics is reported as
+/// failed.
+fn run_detached<T: Into<Answer>>(
+    timeout: Duration,
+    ctx: &Context,
+    part: impl FnOnce(&Context) -> Result<T> + Send + 'static,
+) -> PartReport {
+    let ctx = ctx.for_part();
+    let worker_ctx = ctx.clone();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let start = Instant::now();
+        let report = capture(|| {
+            panic::catch_unwind(AssertUnwindSafe(|| timed(|| part(&worker_ctx)))).unwrap_or_else(
+                |payload| PartReport {
+                    answer: Err(anyhow!("the part panicked: {}", panic_message(&*payload))),
+                    time: start.elapsed(),
+                    alloc: None,
+                },
+            )
+        });
+        // the receiver is gone if the part timed out
+        let _ = sender.send(report);
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok((report, output)) => {
+            print_captured(&output);
+            report
+        }
+        Err(RecvTimeoutError::Timeout) => {
+            ctx.cancel();
+            PartReport {
+                answer: Err(Timeout(timeout).into()),
+                time: timeout,
+                alloc: None,
+            }
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+            unreachable!("the worker sends a report even when the part panics")
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("no message")
+}
+
+/// Parses a `--timeout` such as `500ms`, `1.5s` or `2m`, in seconds without a unit
+pub fn parse_timeout(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value = value
+        .parse::<f64>()
+        .with_context(|| format!("invalid duration '{s}'"))?;
+    let seconds = match unit.trim() {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" | "min" => value * 60.0,
+        unit => bail!("unknown unit '{unit}' in duration '{s}', expected ms, s or m"),
+    };
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+fn timed<T: Into<Answer>>(part: impl FnOnce() -> Result<T>) -> PartReport {
+    let start = Instant::now();
+    let (answer, alloc) = alloc::measure(|| part().map(Into::into));
+    PartReport {
+        answer,
+        time: start.elapsed(),
+        alloc,
+    }
+}
+
+pub fn print_results(report: DayReport) -> Result<()> {
+    if let Some(bench) = &report.bench {
+        print_bench(bench);
+    }
+    let t1 = report.part1.as_ref().map(|p| (p.time, p.alloc));
+    let t2 = report.part2.as_ref().map(|p| (p.time, p.alloc));
+    println!("\nResults:");
+    let mut timeout = None;
+    for (name, part) in [("part1", report.part1), ("part2", report.part2)] {
+        match part.map(|p| p.answer) {
+            Some(Err(err)) if err.is::<Timeout>() => {
+                println!("    {name}: TIMEOUT");
+                timeout = Some(err);
+            }
+            Some(answer) => print_answer(name, &answer?),
+            None => {}
+        }
+    }
+    println!("\nTimings:");
+    println!(
+        "    parse: {:?}{}",
+        report.parse_time,
+        alloc_suffix(report.parse_alloc)
+    );
+    if let Some((t, alloc)) = t1 {
+        println!("    part1: {:?}{}", t, alloc_suffix(alloc));
+    }
+    if let Some((t, alloc)) = t2 {
+        println!("    part2: {:?}{}", t, alloc_suffix(alloc));
+    }
+    timeout.map_or(Ok(()), Err)
+}
+
+fn print_answer(name: &str, answer: &Answer) {
+    match answer {
+        Answer::Grid(grid) => {
+            println!("    {name}:");
+            grid.lines().for_each(|line| println!("        {line}"));
+        }
+        answer => println!("    {name}: {answer}"),
+    }
+}
+
+fn alloc_suffix(alloc: Option<AllocStats>) -> String {
+    alloc.map_or(String::new(), |alloc| format!(" ({alloc})"))
+}
+
+/// Prints one line per day with the answers and timings of each part
+pub fn print_summary(reports: &[(u32, Result<DayReport>)]) {
+    let rows = reports
+        .iter()
+        .map(|(day, report)| {
+            let mut row = vec![day.to_string()];
+            match report {
+                Ok(report) => {
+                    let parts = [&report.part1, &report.part2];
+                    row.extend(
+                        parts
+                            .iter()
+                            .map(|p| p.as_ref().map_or(String::new(), answer_or_error)),
+                    );
+                    row.push(format!("{:?}", report.parse_time));
+                    row.extend(parts.iter().map(|p| {
+                        p.as_ref()
+                            .map_or(String::new(), |p| format!("{:?}", p.time))
+                    }));
+                }
+                Err(err) => row.push(format!("error: {}", first_line(err))),
+            }
+            row
+        })
+        .collect::<Vec<_>>();
+    let header = ["day", "part1", "part2", "parse", "part1", "part2"].map(String::from);
+    let widths = header
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            rows.iter()
+                .filter(|row| row.len() == header.len())
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.chars().count())
+                .chain([h.len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+    println!("\nSummary:");
+    for row in [header.to_vec()].iter().chain(&rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, width))| {
+                if i == 0 {
+                    format!("{cell:>width$}")
+                } else {
+                    format!("{cell:<width$}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("    {}", line.trim_end());
+    }
+}
+
+fn answer_or_error(part: &PartReport) -> String {
+    match &part.answer {
+        Ok(answer) => answer.one_line(),
+        Err(err) if err.is::<Timeout>() => String::from("TIMEOUT"),
+        Err(err) => format!("error: {}", first_line(err)),
+    }
+}
+
+/// First line of an error, the excerpt of a located [`ParseError`](crate::ParseError) does not
+/// fit in a table
+fn first_line(err: &anyhow::Error) -> String {
+    let err = err.to_string();
+    String::from(err.lines().next().unwrap_or_default())
+}
+
+/// Prints the total time of a run of several days, its slowest parts and its errors
+pub fn print_totals(reports: &[(u32, Result<DayReport>)], wall_time: Duration) {
+    let parts = reports
+        .iter()
+        .filter_map(|(day, report)| Some((day, report.as_ref().ok()?)))
+        .flat_map(|(day, report)| {
+            [("part1", &report.part1), ("part2", &report.part2)]
+                .into_iter()
+                .filter_map(move |(name, part)| Some((*day, name, part.as_ref()?)))
+        })
+        .collect::<Vec<_>>();
+    let cpu_time = reports
+        .iter()
+        .filter_map(|(_, report)| Some(report.as_ref().ok()?.parse_time))
+        .chain(parts.iter().map(|(_, _, part)| part.time))
+        .sum::<Duration>();
+    println!(
+        "\nTotal: {} days in {wall_time:.2?} (parse and parts: {cpu_time:.2?})",
+        reports.len()
+    );
+    println!("\nSlowest parts:");
+    for (day, name, part) in parts
+        .iter()
+        .sorted_by_key(|(_, _, part)| Reverse(part.time))
+        .take(SLOWEST_PARTS)
+    {
+        println!("    day {day:>2} {name}: {:.2?}", part.time);
+    }
+    print_errors(reports);
+}
+
+/// Prints the errors of the days and of their parts, with the excerpt of located parse errors
+pub fn print_errors(reports: &[(u32, Result<DayReport>)]) {
+    let errors = reports
+        .iter()
+        .flat_map(|(day, report)| match report {
+            Err(err) => vec![format!("day {day:>2}: {err:#}")],
+            Ok(report) => [("part1", &report.part1), ("part2", &report.part2)]
+                .into_iter()
+                .filter_map(|(name, part)| {
+                    let err = part.as_ref()?.answer.as_ref().err()?;
+                    Some(format!("day {day:>2} {name}: {err:#}"))
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        println!("\nErrors:");
+        for error in errors {
+            println!("    {}", error.replace('\n', "\n    "));
+        }
+    }
+}
+
+/// Number of parts listed by [`print_totals`]
+const SLOWEST_PARTS: usize = 5;
+
+#[cfg(test)]
+mod test_runner {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        runner::{parse_timeout, run_detached, Timeout},
+        Context, Result,
+    };
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_timeout("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_timeout("2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_timeout("10").unwrap(), Duration::from_secs(10));
+        assert!(parse_timeout("10h").is_err());
+        assert!(parse_timeout("s").is_err());
+    }
+
+    #[test]
+    fn test_run_detached() {
+        // printing the panic, with its backtrace if enabled, can take a while
+        let report = run_detached(
+            Duration::from_secs(10),
+            &Context::default(),
+            |_| -> Result<u32> { panic!("no answer") },
+        );
+        assert_eq!(
+            format!("{:#}", report.answer.unwrap_err()),
+            "the part panicked: no answer"
+        );
+
+        let start = Instant::now();
+        let report = run_detached(
+            Duration::from_millis(50),
+            &Context::default(),
+            |_| -> Result<u32> {
+                thread::sleep(Duration::from_secs(5));
+                Ok(1)
+            },
+        );
+        assert!(report.answer.unwrap_err().is::<Timeout>());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
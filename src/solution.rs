@@ -0,0 +1,82 @@
+use crate::{Answer, Context, Result};
+
+/// A puzzle solution: the input is parsed once, then both parts are solved from the parsed input.
+///
+/// The lifetime is the one of the raw input, so that `Input` can borrow from it.
+/// The parts also get the [`Context`] of the run, holding the puzzle parameters.
+pub trait Solution<'a> {
+    type Input;
+    type Output1: Into<Answer>;
+    type Output2: Into<Answer>;
+
+    fn parse(&self, input: &'a str) -> Result<Self::Input>;
+    fn part1(&self, input: &Self::Input, ctx: &Context) -> Result<Self::Output1>;
+    fn part2(&self, input: &Self::Input, ctx: &Context) -> Result<Self::Output2>;
+}
+
+/// Adapter implementing [`Solution`] on top of the `parse`, `part1` and `part2` functions of a day
+pub struct FnSolution<P, P1, P2> {
+    parse: P,
+    part1: P1,
+    part2: P2,
+}
+
+impl<P, P1, P2> FnSolution<P, P1, P2> {
+    // the bounds let the compiler infer the argument type of the part closures
+    pub fn new<'a, Input, A, B>(parse: P, part1: P1, part2: P2) -> Self
+    where
+        P: Fn(&'a str) -> Result<Input>,
+        P1: Fn(&Input, &Context) -> Result<A>,
+        P2: Fn(&Input, &Context) -> Result<B>,
+    {
+        FnSolution {
+            parse,
+            part1,
+            part2,
+        }
+    }
+}
+
+impl<'a, Input, A, B, P, P1, P2> Solution<'a> for FnSolution<P, P1, P2>
+where
+    A: Into<Answer>,
+    B: Into<Answer>,
+    P: Fn(&'a str) -> Result<Input>,
+    P1: Fn(&Input, &Context) -> Result<A>,
+    P2: Fn(&Input, &Context) -> Result<B>,
+{
+    type Input = Input;
+    type Output1 = A;
+    type Output2 = B;
+
+    fn parse(&self, input: &'a str) -> Result<Input> {
+        (self.parse)(input)
+    }
+    fn part1(&self, input: &Input, ctx: &Context) -> Result<A> {
+        (self.part1)(input, ctx)
+    }
+    fn part2(&self, input: &Input, ctx: &Context) -> Result<B> {
+        (self.part2)(input, ctx)
+    }
+}
+
+/// Builds the [`Solution`] of a day from the `parse`, `part1` and `part2` functions of the given module.
+///
+/// With `with_context`, the parts take the [`Context`] as a second argument.
+#[macro_export]
+macro_rules! day_solution {
+    ($module:ident) => {
+        aoc23::FnSolution::new(
+            $module::parse,
+            |parsed, _| $module::part1(parsed),
+            |parsed, _| $module::part2(parsed),
+        )
+    };
+    ($module:ident, with_context) => {
+        aoc23::FnSolution::new(
+            $module::parse,
+            |parsed, ctx| $module::part1(parsed, ctx),
+            |parsed, ctx| $module::part2(parsed, ctx),
+        )
+    };
+}
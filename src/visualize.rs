@@ -0,0 +1,66 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    thread::sleep,
+    time::Duration,
+};
+
+use crossterm_cursor::cursor;
+
+use crate::{raster::Image, Result};
+
+/// Backend showing the frames emitted by a day through [`Context::frame`](crate::Context::frame)
+pub trait Visualizer: Send {
+    /// Called before the first frame
+    fn begin(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn frame(&mut self, frame: &dyn Display) -> Result<()>;
+
+    /// Called for the frames drawn as images, which the text backends skip
+    fn image(&mut self, _image: &Image) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called once the parts are done
+    fn end(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Draws each frame over the previous one in the terminal, waiting `delay` after each frame
+pub struct TerminalVisualizer {
+    delay: Duration,
+    /// Number of lines of the previous frame
+    lines: usize,
+}
+
+impl TerminalVisualizer {
+    pub fn new(delay: Duration) -> Self {
+        TerminalVisualizer { delay, lines: 0 }
+    }
+}
+
+impl Visualizer for TerminalVisualizer {
+    fn frame(&mut self, frame: &dyn Display) -> Result<()> {
+        let mut frame = frame.to_string();
+        if !frame.ends_with('\n') {
+            frame.push('\n');
+        }
+        if self.lines > 0 {
+            cursor().move_up(self.lines as u16)?;
+        }
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()?;
+        self.lines = frame.matches('\n').count();
+        sleep(self.delay);
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<()> {
+        self.lines = 0;
+        Ok(())
+    }
+}